@@ -0,0 +1,82 @@
+//! Per-channel arithmetic on colors, clamping the results to the 0..1 range.
+
+use image::{Pixel, Rgb};
+
+use crate::Color;
+
+/// Adds two colors, clamping each channel to 0..1.
+pub fn add(a: &Color, b: &Color) -> Color {
+    a.map2(b, |a, b| (a + b).clamp(0.0, 1.0))
+}
+
+/// Subtracts the second color from the first, clamping each channel to 0..1.
+pub fn sub(a: &Color, b: &Color) -> Color {
+    a.map2(b, |a, b| (a - b).clamp(0.0, 1.0))
+}
+
+/// Multiplies two colors, clamping each channel to 0..1.
+pub fn mul(a: &Color, b: &Color) -> Color {
+    a.map2(b, |a, b| (a * b).clamp(0.0, 1.0))
+}
+
+/// Multiplies every channel of a color by a factor, clamping each channel to 0..1.
+pub fn scale(color: &Color, factor: f32) -> Color {
+    color.map(|v| (v * factor).clamp(0.0, 1.0))
+}
+
+/// Clamps every channel of a color to 0..1.
+pub fn clamp01(color: &Color) -> Color {
+    color.map(|v| v.clamp(0.0, 1.0))
+}
+
+/// Linearly interpolates between two colors, where a fraction of 0 gives the first color and 1 the second.
+/// The fraction is clamped to 0..1, so the result never leaves the range spanned by both colors.
+pub fn mix(a: &Color, b: &Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    Rgb([0, 1, 2].map(|c| a.0[c] + (b.0[c] - a.0[c]) * t))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use crate::arithmetic::{add, clamp01, mix, mul, scale, sub};
+
+    #[test]
+    fn add_clamps() {
+        assert_eq!(Rgb([0.5, 1.0, 0.75]), add(&Rgb([0.25, 0.75, 0.5]), &Rgb([0.25, 0.5, 0.25])));
+    }
+
+    #[test]
+    fn sub_clamps() {
+        assert_eq!(Rgb([0.25, 0.0, 0.0]), sub(&Rgb([0.5, 0.25, 0.0]), &Rgb([0.25, 0.5, 0.0])));
+    }
+
+    #[test]
+    fn mul_clamps() {
+        assert_eq!(Rgb([0.25, 1.0, 0.0]), mul(&Rgb([0.5, 1.0, 0.5]), &Rgb([0.5, 2.0, -1.0])));
+    }
+
+    #[test]
+    fn scale_clamps() {
+        assert_eq!(Rgb([0.5, 1.0, 0.0]), scale(&Rgb([0.25, 0.75, 0.0]), 2.0));
+        assert_eq!(Rgb([0.0, 0.0, 0.0]), scale(&Rgb([0.25, 0.75, 1.0]), -1.0));
+    }
+
+    #[test]
+    fn clamp_boundaries() {
+        assert_eq!(Rgb([0.0, 0.5, 1.0]), clamp01(&Rgb([-0.5, 0.5, 1.5])));
+        assert_eq!(Rgb([0.0, 1.0, 1.0]), clamp01(&Rgb([0.0, 1.0, 1.0])));
+    }
+
+    #[test]
+    fn mix_fractions() {
+        let a = Rgb([0.0, 0.5, 1.0]);
+        let b = Rgb([1.0, 0.5, 0.0]);
+        assert_eq!(a, mix(&a, &b, 0.0));
+        assert_eq!(Rgb([0.5, 0.5, 0.5]), mix(&a, &b, 0.5));
+        assert_eq!(b, mix(&a, &b, 1.0));
+        assert_eq!(b, mix(&a, &b, 2.0));
+        assert_eq!(a, mix(&a, &b, -1.0));
+    }
+}
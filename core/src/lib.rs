@@ -1,6 +1,8 @@
 use image::Rgb;
 use interpolation::lerp;
 
+pub mod arithmetic;
+
 pub type Color = Rgb<f32>;
 
 pub trait ImageOperation {
@@ -8,6 +10,7 @@ pub trait ImageOperation {
 }
 
 /// A linear gradient between 2 colors.
+#[derive(Clone, Debug)]
 pub struct Gradient {
     pub start: Color,
     pub end: Color
@@ -17,9 +20,254 @@ impl Gradient {
     pub fn get_color(&self, fraction: f32) -> Color {
         Rgb(lerp(&self.start.0, &self.end.0, &fraction))
     }
+
+    /// Describes the gradient by the hex strings of its colors, such as `#000000→#ff0000`.
+    pub fn describe(&self) -> String {
+        format!("{}→{}", color_to_hex(&self.start), color_to_hex(&self.end))
+    }
+}
+
+/// A gradient where each channel ramps independently between its own start and end value.
+#[derive(Clone, Debug)]
+pub struct ChannelGradient {
+    /// The red value at the start and end of the gradient.
+    pub r: (f32, f32),
+    /// The green value at the start and end of the gradient.
+    pub g: (f32, f32),
+    /// The blue value at the start and end of the gradient.
+    pub b: (f32, f32),
+}
+
+impl ChannelGradient {
+    pub fn get_color(&self, fraction: f32) -> Color {
+        Rgb([self.r, self.g, self.b].map(|(start, end)| lerp(&start, &end, &fraction)))
+    }
+}
+
+impl From<Gradient> for ChannelGradient {
+    fn from(gradient: Gradient) -> Self {
+        let [r, g, b] = [0, 1, 2].map(|c| (gradient.start.0[c], gradient.end.0[c]));
+        ChannelGradient { r, g, b }
+    }
 }
 
 pub fn average_color(color: &Color) -> f32 {
     let [r, g, b] = color.0;
     (r+g+b) / 3.0
 }
+
+/// The Euclidean distance between two colors in RGB space.
+pub fn color_distance(a: &Color, b: &Color) -> f32 {
+    a.0.iter().zip(b.0).map(|(a, b)| (a - b) * (a - b)).sum::<f32>().sqrt()
+}
+
+/// Returns the index of the palette color closest to the target, by `color_distance`.
+/// When several colors are equally close the first of them is returned. Returns None if the palette is empty.
+pub fn nearest_color(target: &Color, palette: &[Color]) -> Option<usize> {
+    palette.iter()
+        .map(|color| color_distance(target, color))
+        .enumerate()
+        .fold(None, |nearest: Option<(usize, f32)>, (i, distance)| match nearest {
+            Some((_, best)) if best <= distance => nearest,
+            _ => Some((i, distance)),
+        })
+        .map(|(i, _)| i)
+}
+
+/// Formats a color as a `#rrggbb` hex string, with every channel clamped between 0 and 1.
+pub fn color_to_hex(color: &Color) -> String {
+    let [r, g, b] = color.0.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Parses a color from a `#rrggbb` or shorthand `#rgb` hex string, as written by `color_to_hex`.
+/// The `#` may be left out, and the digits are case insensitive.
+pub fn color_from_hex(s: &str) -> Result<Color, String> {
+    let digits = s.strip_prefix('#').unwrap_or(s);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex color {:?}, expected only hex digits", s));
+    }
+    let channels = match digits.len() {
+        3 => digits.chars().map(|c| c.to_digit(16).unwrap() * 17).collect::<Vec<_>>(),
+        6 => (0..6).step_by(2).map(|i| u32::from_str_radix(&digits[i..i + 2], 16).unwrap()).collect(),
+        len => return Err(format!("Invalid hex color {:?}, expected 3 or 6 digits but found {}", s, len)),
+    };
+    Ok(Rgb([channels[0], channels[1], channels[2]].map(|v| v as f32 / 255.0)))
+}
+
+/// The perceived brightness of a color, using the Rec. 709 weights.
+pub fn luminance(color: &Color) -> f32 {
+    let [r, g, b] = color.0;
+    0.2126*r + 0.7152*g + 0.0722*b
+}
+
+/// The way the brightness of a color is measured when comparing it to a threshold.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Brightness {
+    /// The unweighted average of the channels.
+    #[default]
+    Average,
+    /// The perceptual luminance of the color.
+    Luminance,
+}
+
+impl Brightness {
+    /// Measures the brightness of a color.
+    pub fn of(&self, color: &Color) -> f32 {
+        match self {
+            Brightness::Average => average_color(color),
+            Brightness::Luminance => luminance(color),
+        }
+    }
+}
+
+/// Converts a color to hue, saturation and lightness. The hue is in degrees in 0..360, the others are in 0..1.
+/// Grays have no hue or saturation, so both are 0.
+pub fn rgb_to_hsl(color: &Color) -> (f32, f32, f32) {
+    let [r, g, b] = color.0;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let chroma = max - min;
+    if chroma == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = chroma / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    (hue * 60.0, saturation, lightness)
+}
+
+/// Converts a hue in degrees, saturation and lightness to a color. Hues outside of 0..360 wrap around.
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> Color {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    Rgb([r + m, g + m, b + m])
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use crate::{average_color, color_distance, nearest_color, hsl_to_rgb, ChannelGradient, Gradient, luminance, rgb_to_hsl, color_from_hex, color_to_hex, Brightness, Color};
+
+    #[test]
+    fn luminance_green() {
+        let green = Rgb([0.0, 1.0, 0.0]);
+        assert!((average_color(&green) - 1.0 / 3.0).abs() < 1e-6);
+        assert!((luminance(&green) - 0.7152).abs() < 1e-6);
+        assert!(luminance(&green) > 2.0 * average_color(&green));
+    }
+
+    #[test]
+    fn luminance_white() {
+        let white = Rgb([1.0, 1.0, 1.0]);
+        assert!((luminance(&white) - 1.0).abs() < 1e-6);
+        assert!((Brightness::Average.of(&white) - Brightness::Luminance.of(&white)).abs() < 1e-6);
+    }
+
+    fn assert_color_eq(expected: Color, actual: Color) {
+        assert!(expected.0.iter().zip(actual.0).all(|(a, b)| (a - b).abs() < 1e-5), "{:?} != {:?}", expected, actual);
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for color in [Rgb([1.0, 0.0, 0.0]), Rgb([0.2, 0.6, 0.4]), Rgb([0.9, 0.8, 0.1]), Rgb([0.3, 0.1, 0.7]), Rgb([0.5, 0.5, 0.5])] {
+            let (h, s, l) = rgb_to_hsl(&color);
+            assert!((0.0..360.0).contains(&h));
+            assert_color_eq(color, hsl_to_rgb(h, s, l));
+        }
+        let (h, s, l) = rgb_to_hsl(&Rgb([0.0, 0.0, 1.0]));
+        assert!((h - 240.0).abs() < 1e-4 && (s - 1.0).abs() < 1e-6 && (l - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hsl_gray() {
+        assert_eq!((0.0, 0.0, 0.25), rgb_to_hsl(&Rgb([0.25, 0.25, 0.25])));
+        assert_color_eq(Rgb([0.25, 0.25, 0.25]), hsl_to_rgb(120.0, 0.0, 0.25));
+    }
+
+    #[test]
+    fn hsl_lighter() {
+        let color = Rgb([0.2, 0.6, 0.4]);
+        let (h, s, l) = rgb_to_hsl(&color);
+        let lighter = hsl_to_rgb(h, s, l + 0.2);
+        assert!(lighter.0.iter().zip(color.0).all(|(a, b)| *a > b));
+        assert_color_eq(Rgb([1.0, 1.0, 1.0]), hsl_to_rgb(h, s, 1.0));
+    }
+
+    #[test]
+    fn channel_gradient() {
+        let gradient = ChannelGradient { r: (0.0, 1.0), g: (0.5, 0.5), b: (1.0, 0.0) };
+        assert_color_eq(Rgb([0.0, 0.5, 1.0]), gradient.get_color(0.0));
+        assert_color_eq(Rgb([0.5, 0.5, 0.5]), gradient.get_color(0.5));
+        assert_color_eq(Rgb([1.0, 0.5, 0.0]), gradient.get_color(1.0));
+    }
+
+    #[test]
+    fn channel_gradient_from_gradient() {
+        let gradient = Gradient { start: Rgb([0.0, 0.2, 0.4]), end: Rgb([1.0, 0.6, 0.0]) };
+        let channels = ChannelGradient::from(gradient.clone());
+        for fraction in [0.0, 0.5, 1.0] {
+            assert_color_eq(gradient.get_color(fraction), channels.get_color(fraction));
+        }
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!("#ff8000", color_to_hex(&Rgb([1.0, 0.5, 0.0])));
+        assert_eq!("#00ff00", color_to_hex(&Rgb([-1.0, 2.0, 0.0])));
+        let gradient = Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 0.0, 0.0]) };
+        assert_eq!("#000000→#ff0000", gradient.describe());
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        for hex in ["#000000", "#ffffff", "#ff8000", "#12abef"] {
+            assert_eq!(hex, color_to_hex(&color_from_hex(hex).unwrap()));
+        }
+        assert_eq!(Ok(Rgb([1.0, 0.0, 0.0])), color_from_hex("FF0000"));
+        assert_eq!(color_from_hex("#ff6600"), color_from_hex("#f60"));
+    }
+
+    #[test]
+    fn hex_errors() {
+        for malformed in ["", "#", "#ff00", "#ff00000", "#gg0000", "#ff 000", "##ff0000", "#ff00é"] {
+            assert!(color_from_hex(malformed).is_err(), "{:?} should not parse", malformed);
+        }
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(0.0, color_distance(&Rgb([0.2, 0.4, 0.6]), &Rgb([0.2, 0.4, 0.6])));
+        assert_eq!(5.0, color_distance(&Rgb([0.0, 0.0, 0.0]), &Rgb([3.0, 4.0, 0.0])));
+        assert_eq!(color_distance(&Rgb([1.0, 0.0, 0.5]), &Rgb([0.0, 1.0, 0.0])), color_distance(&Rgb([0.0, 1.0, 0.0]), &Rgb([1.0, 0.0, 0.5])));
+    }
+
+    #[test]
+    fn nearest() {
+        let palette = [Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0])];
+        assert_eq!(Some(1), nearest_color(&Rgb([0.8, 0.1, 0.1]), &palette));
+        assert_eq!(Some(2), nearest_color(&Rgb([0.9, 0.8, 0.9]), &palette));
+        // Equally close colors resolve to the first of them.
+        assert_eq!(Some(0), nearest_color(&Rgb([0.5, 0.0, 0.0]), &palette));
+        assert_eq!(Some(1), nearest_color(&Rgb([1.0, 0.5, 0.5]), &palette));
+        assert_eq!(None, nearest_color(&Rgb([0.5, 0.5, 0.5]), &[]));
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+use petgraph::visit::Topo;
+
+use crate::{GraphError, NodeIndex, TextureGraph};
+
+/// A handle to a graph that is being generated on a worker thread.
+/// The graph is returned when the generation is joined, with the results of the generated nodes.
+pub struct GenerateHandle<T> {
+    sink: NodeIndex,
+    cancelled: Arc<AtomicBool>,
+    thread: JoinHandle<(TextureGraph<T>, Result<(), GraphError>)>,
+}
+
+impl<T> GenerateHandle<T> {
+    /// Returns the node that is being generated.
+    pub fn sink(&self) -> NodeIndex {
+        self.sink
+    }
+
+    /// Checks whether the worker thread is done, without blocking.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Requests the worker to stop. Generation stops before the next node is generated.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits for the worker thread to finish, and returns the graph with the result of the generation.
+    pub fn join(self) -> (TextureGraph<T>, Result<(), GraphError>) {
+        self.thread.join().expect("Generation thread panicked")
+    }
+}
+
+impl<T: Send + 'static> TextureGraph<T> {
+    /// Moves the graph to a worker thread and generates the given node and all nodes that feed into it.
+    /// Fails if the sink does not exist, any of the nodes can not be generated, or the generation was cancelled.
+    pub fn spawn_generate(self, sink: NodeIndex) -> GenerateHandle<T> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = cancelled.clone();
+        let thread = thread::spawn(move || {
+            let mut graph = self;
+            let result = graph.generate_sink(sink, &flag);
+            (graph, result)
+        });
+        GenerateHandle { sink, cancelled, thread }
+    }
+
+    fn generate_sink(&mut self, sink: NodeIndex, cancelled: &AtomicBool) -> Result<(), GraphError> {
+        if self.get_node(sink).is_none() {
+            return Err(GraphError::UnknownNode(sink));
+        }
+        let mut required: HashSet<NodeIndex> = self.ancestors(sink).into_iter().collect();
+        required.insert(sink);
+        let mut topo = Topo::new(&self.g);
+        while let Some(index) = topo.next(&self.g) {
+            if !required.contains(&index) {
+                continue
+            }
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(GraphError::Cancelled);
+            }
+            self.generate_node(index)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{TextureGraph, Node};
+    use crate::fixtures::{Add, Const};
+
+    #[test]
+    fn spawn_generate() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Const(4))));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        let handle = graph.spawn_generate(index3);
+        while !handle.is_finished() {
+            std::thread::yield_now();
+        }
+        assert_eq!(index3, handle.sink());
+        let (mut graph, result) = handle.join();
+        assert!(result.is_ok());
+        assert_eq!(4, graph.node_count());
+        assert_eq!(Some(&3), graph.get_generated_node(&index3));
+        assert!(graph.get_generated_node(&index4).is_none());
+    }
+
+    #[test]
+    fn spawn_generate_unknown_sink() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let mut other = TextureGraph::<i32>::new();
+        other.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let sink = other.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let (mut graph, result) = graph.spawn_generate(sink).join();
+        assert!(result.is_err());
+        assert!(graph.get_generated_node(&index).is_none());
+    }
+}
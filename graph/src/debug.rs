@@ -0,0 +1,82 @@
+use petgraph::visit::EdgeRef;
+
+use crate::TextureGraph;
+
+/// Quotes a string as a JSON string, escaping the characters JSON does not allow in strings.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl<T> TextureGraph<T> {
+    /// Describes the state of the graph as JSON, to attach to bug reports.
+    /// Lists every node with its index, name, description, amount of inputs, completeness and whether it has a cached result,
+    /// followed by every edge. Results themselves are not included, see `debug_dump_with` to summarize them.
+    pub fn debug_dump(&self) -> String {
+        self.debug_dump_with(|_| None)
+    }
+
+    /// Describes the state of the graph as JSON like `debug_dump`, adding a summary of every cached result.
+    /// The summary is written as a string, or as null where `summarize` returns None.
+    pub fn debug_dump_with(&self, summarize: impl Fn(&T) -> Option<String>) -> String {
+        let nodes: Vec<_> = self.g.node_indices()
+            .map(|index| {
+                let node = &self.g[index];
+                let result = self.results.get(&index);
+                let summary = match result.and_then(&summarize) {
+                    Some(summary) => json_string(&summary),
+                    None => String::from("null"),
+                };
+                format!(
+                    "    {{\"index\": {}, \"name\": {}, \"description\": {}, \"inputs\": {}, \"complete\": {}, \"cached\": {}, \"summary\": {}}}",
+                    index.index(),
+                    json_string(&node.name),
+                    json_string(&node.function.describe()),
+                    node.function.inputs(),
+                    !self.incomplete.contains(&index),
+                    result.is_some(),
+                    summary,
+                )
+            })
+            .collect();
+        let edges: Vec<_> = self.g.edge_references()
+            .map(|edge| format!("    {{\"source\": {}, \"target\": {}, \"input\": {}}}", edge.source().index(), edge.target().index(), edge.weight()))
+            .collect();
+        format!("{{\n  \"nodes\": [\n{}\n  ],\n  \"edges\": [\n{}\n  ]\n}}", nodes.join(",\n"), edges.join(",\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Node, TextureGraph};
+    use crate::fixtures::{Const, Double};
+
+    #[test]
+    fn debug_dump() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("Base \"one\""), Box::new(Const(3))));
+        let index2 = graph.add_node(Node::new(String::from("Doubled"), Box::new(Double{})));
+        graph.add_node(Node::new(String::from("Unconnected"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.generate_target(index2).unwrap();
+        let dump = graph.debug_dump();
+        assert!(dump.contains(r#"{"index": 0, "name": "Base \"one\"", "description": "Const", "inputs": 0, "complete": true, "cached": true, "summary": null}"#));
+        assert!(dump.contains(r#""name": "Unconnected", "description": "Double", "inputs": 1, "complete": false, "cached": false"#));
+        assert!(dump.contains(r#"{"source": 0, "target": 1, "input": 0}"#));
+        let dump = graph.debug_dump_with(|result| Some(format!("value {}", result)));
+        assert!(dump.contains(r#""cached": true, "summary": "value 6"}"#));
+        assert!(dump.contains(r#""cached": false, "summary": null}"#));
+    }
+}
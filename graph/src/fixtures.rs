@@ -0,0 +1,58 @@
+//! Small transformers on integers, shared by the tests of the graph and its modules.
+
+use crate::TextureTransformer;
+
+/// Produces a constant value.
+#[derive(Clone)]
+pub struct Const(pub i32);
+impl TextureTransformer<i32> for Const {
+    fn generate(&self, _inputs: Vec<&i32>) -> i32 {
+        self.0
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+/// Sums its two inputs.
+#[derive(Clone)]
+pub struct Add{}
+impl TextureTransformer<i32> for Add {
+    fn generate(&self, inputs: Vec<&i32>) -> i32 {
+        inputs.iter().copied().sum()
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        0
+    }
+}
+
+/// Doubles its input, which must not be negative.
+#[derive(Clone)]
+pub struct Double{}
+impl TextureTransformer<i32> for Double {
+    fn generate(&self, inputs: Vec<&i32>) -> i32 {
+        inputs[0] * 2
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        0
+    }
+
+    fn is_valid(&self, inputs: &Vec<&i32>) -> bool {
+        !inputs.is_empty() && *inputs[0] >= 0
+    }
+}
@@ -0,0 +1,276 @@
+use petgraph::Direction::Outgoing;
+use petgraph::visit::EdgeRef;
+
+use crate::{GraphError, Node, NodeHandle, NodeIndex, TextureGraph, TextureTransformer};
+
+/// An edge of a graph, as the handles of its source and destination node and the target input.
+type EdgeHandles = (NodeHandle, NodeHandle, usize);
+
+/// A single change to the structure of a graph. Applying a change returns the change that reverts it.
+/// Nodes are referred to by handle, as their indices change when other nodes are removed.
+enum Change<T> {
+    Insert { node: Node<T>, edges: Vec<EdgeHandles> },
+    Remove(NodeHandle),
+    Connect { src: NodeHandle, dest: NodeHandle, input: usize },
+    Disconnect { dest: NodeHandle, input: usize },
+    SetFunction { node: NodeHandle, function: Box<dyn TextureTransformer<T>> },
+}
+
+fn index<T>(graph: &TextureGraph<T>, handle: NodeHandle) -> Result<NodeIndex, GraphError> {
+    graph.resolve(handle)
+}
+
+fn handle<T>(graph: &TextureGraph<T>, index: NodeIndex) -> Result<NodeHandle, GraphError> {
+    graph.handle_of(index).ok_or(GraphError::UnknownNode(index))
+}
+
+impl<T> Change<T> {
+    /// Applies the change to the graph, and returns the change that reverts it.
+    /// Fails without changing the graph, so the change can be kept to try again later.
+    fn apply(&self, graph: &mut TextureGraph<T>) -> Result<Change<T>, GraphError> {
+        match self {
+            Change::Insert { node, edges } => {
+                let handle = node.handle;
+                let inserted = graph.insert_node(node.clone());
+                let connected = edges.iter().try_for_each(|&(src, dest, input)| {
+                    let dest = index(graph, dest)?;
+                    // Replacing an edge could not be reverted by removing the node again.
+                    if graph.input_connections(dest).iter().any(|(target, _)| *target == input) {
+                        return Err(GraphError::AlreadyConnected { node: dest, input });
+                    }
+                    graph.add_edge(index(graph, src)?, dest, input)
+                });
+                if let Err(e) = connected {
+                    graph.remove_node(inserted)?;
+                    return Err(e);
+                }
+                Ok(Change::Remove(handle))
+            },
+            Change::Remove(node) => {
+                let removed = index(graph, *node)?;
+                let mut edges = Vec::new();
+                for (input, src) in graph.input_connections(removed) {
+                    edges.push((handle(graph, src)?, *node, input));
+                }
+                for edge in graph.g.edges_directed(removed, Outgoing) {
+                    edges.push((*node, graph.g[edge.target()].handle, *edge.weight()));
+                }
+                let node = graph.remove_node(removed)?;
+                Ok(Change::Insert { node, edges })
+            },
+            Change::Connect { src, dest, input } => {
+                let old = graph.replace_input_subtree(index(graph, *dest)?, *input, index(graph, *src)?)?;
+                match old {
+                    Some(old) => Ok(Change::Connect { src: graph.g[old].handle, dest: *dest, input: *input }),
+                    None => Ok(Change::Disconnect { dest: *dest, input: *input }),
+                }
+            },
+            Change::Disconnect { dest, input } => {
+                let dest_index = index(graph, *dest)?;
+                let src = graph.input_connections(dest_index).into_iter()
+                    .find(|(target, _)| target == input)
+                    .map(|(_, src)| graph.g[src].handle);
+                graph.disconnect_edge(dest_index, *input)?;
+                Ok(Change::Connect { src: src.unwrap(), dest: *dest, input: *input })
+            },
+            Change::SetFunction { node, function } => {
+                let node_index = index(graph, *node)?;
+                let old = graph.g[node_index].function.clone_box();
+                graph.set_function(node_index, function.clone_box())?;
+                Ok(Change::SetFunction { node: *node, function: old })
+            },
+        }
+    }
+}
+
+/// Wraps a graph to record changes to its structure, so they can be undone and redone.
+/// Only the changes are stored rather than copies of the graph, so every step is cheap.
+pub struct History<T> {
+    graph: TextureGraph<T>,
+    undo_stack: Vec<Change<T>>,
+    redo_stack: Vec<Change<T>>,
+}
+
+impl<T> History<T> {
+    /// Starts recording changes to the graph. Changes made before are not recorded.
+    pub fn new(graph: TextureGraph<T>) -> Self {
+        History { graph, undo_stack: Vec::new(), redo_stack: Vec::new() }
+    }
+
+    /// Returns the graph with all changes applied.
+    pub fn graph(&self) -> &TextureGraph<T> {
+        &self.graph
+    }
+
+    /// Returns the graph mutably, for example to generate it.
+    /// Changes to the structure made through this reference are not recorded, and may make undoing fail.
+    pub fn graph_mut(&mut self) -> &mut TextureGraph<T> {
+        &mut self.graph
+    }
+
+    /// Stops recording and returns the graph.
+    pub fn into_graph(self) -> TextureGraph<T> {
+        self.graph
+    }
+
+    /// Applies a change and records how to revert it. Any undone changes can no longer be redone.
+    fn record(&mut self, change: Change<T>) -> Result<(), GraphError> {
+        let inverse = change.apply(&mut self.graph)?;
+        self.undo_stack.push(inverse);
+        self.redo_stack.clear();
+        Ok(())
+    }
+
+    /// Adds a node to the graph, see `TextureGraph::add_node`.
+    pub fn add_node(&mut self, node: Node<T>) -> NodeIndex {
+        let index = self.graph.add_node(node);
+        self.undo_stack.push(Change::Remove(self.graph.g[index].handle));
+        self.redo_stack.clear();
+        index
+    }
+
+    /// Removes a node and its edges from the graph, see `TextureGraph::remove_node`.
+    pub fn remove_node(&mut self, index: NodeIndex) -> Result<(), GraphError> {
+        let node = handle(&self.graph, index)?;
+        self.record(Change::Remove(node))
+    }
+
+    /// Connects the output of a node to an input of another node, see `TextureGraph::add_edge`.
+    pub fn add_edge(&mut self, src: NodeIndex, dest: NodeIndex, target_input: usize) -> Result<(), GraphError> {
+        let src = handle(&self.graph, src)?;
+        let dest = handle(&self.graph, dest)?;
+        self.record(Change::Connect { src, dest, input: target_input })
+    }
+
+    /// Removes the edge to an input of a node, see `TextureGraph::disconnect_edge`.
+    pub fn disconnect_edge(&mut self, dest: NodeIndex, target_input: usize) -> Result<(), GraphError> {
+        let dest = handle(&self.graph, dest)?;
+        self.record(Change::Disconnect { dest, input: target_input })
+    }
+
+    /// Replaces the transformer of a node, see `TextureGraph::set_function`.
+    pub fn set_function(&mut self, index: NodeIndex, function: Box<dyn TextureTransformer<T>>) -> Result<(), GraphError> {
+        let node = handle(&self.graph, index)?;
+        self.record(Change::SetFunction { node, function })
+    }
+
+    /// Checks whether there is a change that can be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Checks whether there is an undone change that can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Reverts the last change. Returns false if there was nothing to undo.
+    /// If the change can not be reverted, the graph is left as is and the change stays on the undo stack.
+    pub fn undo(&mut self) -> Result<bool, GraphError> {
+        let Some(change) = self.undo_stack.last() else {
+            return Ok(false);
+        };
+        let inverse = change.apply(&mut self.graph)?;
+        self.undo_stack.pop();
+        self.redo_stack.push(inverse);
+        Ok(true)
+    }
+
+    /// Applies the last undone change again. Returns false if there was nothing to redo.
+    /// If the change can not be applied, the graph is left as is and the change stays on the redo stack.
+    pub fn redo(&mut self) -> Result<bool, GraphError> {
+        let Some(change) = self.redo_stack.last() else {
+            return Ok(false);
+        };
+        let inverse = change.apply(&mut self.graph)?;
+        self.redo_stack.pop();
+        self.undo_stack.push(inverse);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GraphError, History, Node, TextureGraph};
+    use crate::fixtures::{Add, Const};
+
+    #[test]
+    fn undo_redo_edges() {
+        let mut history = History::new(TextureGraph::<i32>::new());
+        let index1 = history.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = history.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = history.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        history.add_edge(index1, index3, 0).unwrap();
+        history.add_edge(index1, index3, 1).unwrap();
+        history.add_edge(index2, index3, 1).unwrap();
+        assert_eq!(vec![(0, index1), (1, index2)], history.graph().input_connections(index3));
+        assert!(history.undo().unwrap());
+        assert_eq!(vec![(0, index1), (1, index1)], history.graph().input_connections(index3));
+        assert!(history.undo().unwrap());
+        assert_eq!(vec![(0, index1)], history.graph().input_connections(index3));
+        assert!(history.redo().unwrap());
+        assert!(history.redo().unwrap());
+        assert!(!history.redo().unwrap());
+        assert_eq!(vec![(0, index1), (1, index2)], history.graph().input_connections(index3));
+        history.graph_mut().generate_graph().unwrap();
+        assert_eq!(Some(&3), history.graph_mut().get_generated_node(&index3));
+    }
+
+    #[test]
+    fn undo_remove_node() {
+        let mut history = History::new(TextureGraph::<i32>::new());
+        let index1 = history.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = history.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = history.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        history.add_edge(index1, index3, 0).unwrap();
+        history.add_edge(index2, index3, 1).unwrap();
+        let handle1 = history.graph().handle_of(index1).unwrap();
+        let handle3 = history.graph().handle_of(index3).unwrap();
+        history.remove_node(index1).unwrap();
+        assert_eq!(2, history.graph().node_count());
+        assert_eq!(1, history.graph().edge_count());
+        assert!(history.undo().unwrap());
+        // The restored node keeps its handle and edges, even though its index may differ.
+        let restored = history.graph().index_of(handle1).unwrap();
+        let index3 = history.graph().index_of(handle3).unwrap();
+        assert_eq!(vec![(0, restored), (1, index2)], history.graph().input_connections(index3));
+        history.set_function(restored, Box::new(Const(5))).unwrap();
+        history.graph_mut().generate_graph().unwrap();
+        assert_eq!(Some(&7), history.graph_mut().get_generated_node(&index3));
+        history.undo().unwrap();
+        history.graph_mut().generate_graph().unwrap();
+        assert_eq!(Some(&3), history.graph_mut().get_generated_node(&index3));
+        // Undoing everything leaves an empty graph.
+        while history.undo().unwrap() {}
+        assert!(!history.can_undo());
+        assert_eq!(0, history.graph().node_count());
+    }
+
+    #[test]
+    fn failed_undo_keeps_change() {
+        let mut history = History::new(TextureGraph::<i32>::new());
+        let index1 = history.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = history.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = history.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        history.add_edge(index1, index3, 0).unwrap();
+        history.add_edge(index2, index3, 1).unwrap();
+        let handle1 = history.graph().handle_of(index1).unwrap();
+        let handle3 = history.graph().handle_of(index3).unwrap();
+        history.remove_node(index1).unwrap();
+        // An unrecorded edge takes the input the removed node was connected to, so it can not be restored.
+        let index3 = history.graph().index_of(handle3).unwrap();
+        history.graph_mut().add_edge(index2, index3, 0).unwrap();
+        assert_eq!(Err(GraphError::AlreadyConnected { node: index3, input: 0 }), history.undo());
+        assert!(history.can_undo());
+        assert_eq!(2, history.graph().node_count());
+        assert!(history.graph().index_of(handle1).is_none());
+        assert_eq!(vec![(0, index2), (1, index2)], history.graph().input_connections(index3));
+        // Once the input is free again, the same change can be undone.
+        history.graph_mut().disconnect_edge(index3, 0).unwrap();
+        assert!(history.undo().unwrap());
+        let restored = history.graph().index_of(handle1).unwrap();
+        let index3 = history.graph().index_of(handle3).unwrap();
+        assert_eq!(vec![(0, restored), (1, index2)], history.graph().input_connections(index3));
+        assert!(history.can_redo());
+    }
+}
@@ -1,28 +1,158 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use petgraph::Direction::Incoming;
+use petgraph::Direction::{Incoming, Outgoing};
 use petgraph::algo::is_cyclic_directed;
 use petgraph::prelude::DiGraph;
-use petgraph::visit::{Topo, EdgeRef, Bfs};
+use petgraph::visit::{Topo, EdgeRef, Bfs, Reversed};
+
+mod background;
+mod debug;
+mod history;
+#[cfg(test)]
+mod fixtures;
+
+pub use crate::background::GenerateHandle;
+pub use crate::history::History;
 
 pub type NodeIndex = petgraph::graph::NodeIndex;
 
-pub trait TextureTransformer<T> {
+/// Allows boxed transformers to be cloned. Implemented for every transformer that implements `Clone`.
+pub trait CloneTransformer<T> {
+    /// Returns a boxed copy of the transformer.
+    fn clone_box(&self) -> Box<dyn TextureTransformer<T>>;
+}
+
+impl<T, U: TextureTransformer<T> + Clone + 'static> CloneTransformer<T> for U {
+    fn clone_box(&self) -> Box<dyn TextureTransformer<T>> {
+        Box::new(self.clone())
+    }
+}
+
+/// A function that generates the value of a node. Transformers must be `Send` so graphs can be generated on a worker thread,
+/// and cloneable so nodes can be copied.
+pub trait TextureTransformer<T>: Send + CloneTransformer<T> {
     /// Generates the value of the nodes given its target inputs in the correct order.
     /// Function should assume inputs are valid
     fn generate(&self, inputs: Vec<&T>) -> T;
     /// Returns the amount of inputs this Transformer expects.
     fn inputs(&self) -> usize;
+    /// Returns a label for every input, in the order of the target inputs, so an editor can show what each input is for.
+    /// Defaults to "input 0", "input 1" and so on.
+    fn input_names(&self) -> Vec<Cow<'static, str>> {
+        (0..self.inputs()).map(|i| Cow::Owned(format!("input {}", i))).collect()
+    }
     /// Checks whether the inputs of the node conform. Can be used to see if images are the same size.
     fn is_valid(&self, _inputs: &Vec<&T>) -> bool {
         true
     }
+    /// Returns a hash of the parameters of this transformer, used to detect whether a cached result is still valid.
+    /// Transformers with different parameters must return different hashes, or results generated with the old parameters
+    /// are kept after an edit. Transformers that derive `Debug` can return [`hash_debug`] of themselves.
+    fn params_hash(&self) -> u64;
+    /// Returns a short human-readable summary of the transformer and its parameters, for debugging and tooltips.
+    /// Defaults to the name of the type.
+    fn describe(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        String::from(name.rsplit("::").next().unwrap_or(name))
+    }
+    /// Returns the transformer as a `Resizable` if the size of its output can be changed, such as for generators.
+    /// Used by `TextureGraph::set_resolution`.
+    fn as_resizable(&mut self) -> Option<&mut dyn Resizable> {
+        None
+    }
+    /// Measures the generated output of the transformer, for transformers that report statistics besides their output,
+    /// such as a probe for automatic levels. Used by `TextureGraph::probe_stats`. Defaults to reporting nothing.
+    fn probe(&self, _output: &T) -> Option<ChannelStats> {
+        None
+    }
+    /// Predicts the width and height of the output from the sizes of the inputs, without generating anything.
+    /// Returns None if the size is unknown, or if the input sizes are not accepted.
+    /// Defaults to the size of the first input, which is what most transformers output.
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        input_sizes.first().copied()
+    }
+}
+
+/// Hashes the `Debug` representation of a value. For a transformer that derives `Debug`, this covers every parameter,
+/// including floating point parameters that do not implement `Hash`, so it can be used as its `params_hash`.
+pub fn hash_debug(value: &impl Debug) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", value).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A transformer whose output size is set by its parameters rather than by its inputs.
+pub trait Resizable {
+    /// Sets the width and height of the output of the transformer.
+    fn set_size(&mut self, width: u32, height: u32);
+}
+
+/// An error returned by the methods of a `TextureGraph`, such as a problem found by `TextureGraph::validate`
+/// that prevents the graph from being generated, or an edit that refers to a node that does not exist.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GraphError {
+    /// The node has no incoming edge for the given input.
+    MissingInput { node: NodeIndex, input: usize },
+    /// An edge connects to an input the node does not have, for example an edge into a generator.
+    UnexpectedInput { node: NodeIndex, input: usize },
+    /// The inputs of the node have sizes the node does not accept, as predicted by `TextureTransformer::output_size`.
+    SizeMismatch { node: NodeIndex, sizes: Vec<(u32, u32)> },
+    /// The graph contains a cycle, so there is no order in which it can be generated.
+    Cycle,
+    /// No node exists at the index.
+    UnknownNode(NodeIndex),
+    /// No node has the handle, for example because the node was removed.
+    UnknownHandle(NodeHandle),
+    /// The input of the node has no incoming edge to disconnect.
+    NotConnected { node: NodeIndex, input: usize },
+    /// The input of the node already has an incoming edge, where a free input was required.
+    AlreadyConnected { node: NodeIndex, input: usize },
+    /// The width or height of a resolution is zero.
+    InvalidResolution { width: u32, height: u32 },
+    /// The node that feeds into the given input has not been generated yet.
+    InputNotGenerated { node: NodeIndex, input: usize },
+    /// The transformer of the node rejected its generated inputs, see `TextureTransformer::is_valid`.
+    InvalidInputs { node: NodeIndex },
+    /// The generation was cancelled before it finished.
+    Cancelled,
+}
+
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::MissingInput { node, input } => write!(f, "Node {:?} is missing input {}", node, input),
+            GraphError::UnexpectedInput { node, input } => write!(f, "Node {:?} has no input {}", node, input),
+            GraphError::SizeMismatch { node, sizes } => write!(f, "Node {:?} does not accept inputs of sizes {:?}", node, sizes),
+            GraphError::Cycle => write!(f, "Graph contains a cycle"),
+            GraphError::UnknownNode(node) => write!(f, "Unknown node {:?}", node),
+            GraphError::UnknownHandle(handle) => write!(f, "Unknown handle {:?}", handle),
+            GraphError::NotConnected { node, input } => write!(f, "No edge to input {} of node {:?}", input, node),
+            GraphError::AlreadyConnected { node, input } => write!(f, "Input {} of node {:?} is already connected", input, node),
+            GraphError::InvalidResolution { width, height } => write!(f, "Invalid resolution {}x{}", width, height),
+            GraphError::InputNotGenerated { node, input } => write!(f, "Input {} of node {:?} is not generated", input, node),
+            GraphError::InvalidInputs { node } => write!(f, "Inputs of node {:?} are not valid", node),
+            GraphError::Cancelled => write!(f, "Generation cancelled"),
+        }
+    }
 }
 
+impl std::error::Error for GraphError {}
+
+/// A stable reference to a node in a graph. Unlike a `NodeIndex`, a handle stays valid when other nodes are removed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NodeHandle(u64);
+
 pub struct Node<T> {
     pub name: String,
-    function: Box<dyn TextureTransformer<T>>
+    function: Box<dyn TextureTransformer<T>>,
+    /// The handle of the node, assigned when it is added to a graph.
+    handle: NodeHandle
 }
 
 impl<T> Debug for Node<T> {
@@ -31,46 +161,224 @@ impl<T> Debug for Node<T> {
     }
 }
 
+impl<T> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        Node {
+            name: self.name.clone(),
+            function: self.function.clone_box(),
+            handle: self.handle
+        }
+    }
+}
+
 impl<T> Node<T> {
     pub fn new(name: String, function: Box<dyn TextureTransformer<T>>) -> Self {
         Node {
             name,
-            function: function
+            function,
+            handle: NodeHandle(0)
         }
     }
 }
 
+/// Metrics of a graph, as returned by `TextureGraph::stats`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GraphStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// The amount of nodes with exactly one edge to every input.
+    pub complete_nodes: usize,
+    /// The amount of nodes whose result is currently cached.
+    pub cached_results: usize,
+    /// The amount of nodes on the longest path through the graph, which is the least amount of steps needed to generate it.
+    pub longest_chain: usize,
+    /// The amount of nodes with no outgoing edges.
+    pub output_nodes: usize,
+}
+
+/// Statistics of every channel of a generated value, as reported by `TextureTransformer::probe`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChannelStats {
+    /// The smallest value of every channel.
+    pub min: Vec<f32>,
+    /// The largest value of every channel.
+    pub max: Vec<f32>,
+    /// The mean value of every channel.
+    pub mean: Vec<f32>,
+}
+
 /// Main datastructure for a graph of transformers. Contains all nodes in the system, as well as generated values.
 pub struct TextureGraph<T> {
     g: DiGraph<Node<T>, usize>,
     results: HashMap<NodeIndex, T>,
+    /// The structural hash of each node at the time its result was generated.
+    hashes: HashMap<NodeIndex, u64>,
+    /// The nodes that are missing incoming edges, kept up to date so checking the completeness of the graph is cheap.
+    incomplete: HashSet<NodeIndex>,
+    /// The nodes that were edited, or depend on an edited node, since they were last generated.
+    dirty: HashSet<NodeIndex>,
+    /// The largest amount of results that are kept after a generation, or `None` to keep every result.
+    max_cached_results: Option<usize>,
+    /// The time each result was last generated or used, counted in uses of any result.
+    last_used: HashMap<NodeIndex, u64>,
+    use_clock: u64,
+    /// The index of the node each handle refers to, updated when nodes move because another node was removed.
+    handles: HashMap<NodeHandle, NodeIndex>,
+    next_handle: u64,
     cached: bool
 }
 
+impl<T> Default for TextureGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> TextureGraph<T> {
     /// Creates a new Texture graph with no nodes or edges.
     pub fn new() -> Self{
         TextureGraph {
             g: DiGraph::new(),
             results: HashMap::new(),
+            hashes: HashMap::new(),
+            incomplete: HashSet::new(),
+            dirty: HashSet::new(),
+            max_cached_results: None,
+            last_used: HashMap::new(),
+            use_clock: 0,
+            handles: HashMap::new(),
+            next_handle: 0,
             cached: false
          }
     }
 
+    /// Limits the amount of results that are kept after a generation, to bound the memory used by large graphs.
+    /// When the limit is exceeded, the least recently used results are removed, except for the results of output nodes.
+    /// Removed results are generated again when they are needed by `generate_target`, `regenerate_changed` or `generate_graph_missing`.
+    /// While a generation is running, all intermediate results are kept.
+    pub fn set_max_cached_results(&mut self, max_cached_results: Option<usize>) {
+        self.max_cached_results = max_cached_results;
+        self.evict_results();
+    }
+
     /// Returns the number of nodes in the graph.
     pub fn node_count(&self) -> usize {
         self.g.node_count()
     }
 
+    /// Returns the number of edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.g.edge_count()
+    }
+
+    /// Checks whether a node with the given index exists in the graph.
+    pub fn contains_node(&self, index: NodeIndex) -> bool {
+        self.g.node_weight(index).is_some()
+    }
+
     /// Returns a node in the graph based on the index.
     pub fn get_node(&self, index: NodeIndex) -> Option<&Node<T>> {
         self.g.node_weight(index)
     }
 
     /// Adds a new node to the graph, unconnected to any other nodes.
-    pub fn add_node(&mut self, test_node: Node<T>) -> NodeIndex {
+    pub fn add_node(&mut self, mut test_node: Node<T>) -> NodeIndex {
+        test_node.handle = NodeHandle(self.next_handle);
+        self.next_handle += 1;
+        self.insert_node(test_node)
+    }
+
+    /// Adds a node that keeps the handle it already has, such as a node that was removed from this graph before.
+    pub(crate) fn insert_node(&mut self, node: Node<T>) -> NodeIndex {
         self.cached = false;
-        self.g.add_node(test_node)
+        let handle = node.handle;
+        let index = self.g.add_node(node);
+        self.handles.insert(handle, index);
+        self.update_complete(index);
+        self.dirty.insert(index);
+        index
+    }
+
+    /// Returns the description of the transformer of a node, or None if the node does not exist.
+    pub fn node_description(&self, index: NodeIndex) -> Option<String> {
+        self.g.node_weight(index).map(|node| node.function.describe())
+    }
+
+    /// Returns the labels of the inputs of a node, or None if the node does not exist.
+    pub fn input_names(&self, index: NodeIndex) -> Option<Vec<Cow<'static, str>>> {
+        self.g.node_weight(index).map(|node| node.function.input_names())
+    }
+
+    /// Finds the target input of a node with the given label, to connect an edge by name rather than by position.
+    pub fn input_by_name(&self, index: NodeIndex, name: &str) -> Option<usize> {
+        self.input_names(index)?.iter().position(|input| input == name)
+    }
+
+    /// Returns the stable handle of the node at the given index.
+    pub fn handle_of(&self, index: NodeIndex) -> Option<NodeHandle> {
+        self.g.node_weight(index).map(|node| node.handle)
+    }
+
+    /// Returns the current index of the node with the given handle, or `None` if the node was removed.
+    pub fn index_of(&self, handle: NodeHandle) -> Option<NodeIndex> {
+        self.handles.get(&handle).copied()
+    }
+
+    /// Removes a node and its edges from the graph, and returns it. The results of the nodes it fed into are invalidated.
+    /// The last node of the graph takes over the index of the removed node, so indices of other nodes may change,
+    /// but their handles stay valid.
+    /// Fails if the node does not exist in the graph.
+    pub fn remove_node(&mut self, index: NodeIndex) -> Result<Node<T>, GraphError> {
+        if self.g.node_weight(index).is_none() {
+            return Err(GraphError::UnknownNode(index));
+        }
+        let last = NodeIndex::new(self.g.node_count() - 1);
+        let moved = |nx: NodeIndex| if nx == last { index } else { nx };
+        self.invalidate_nodes(index);
+        for descendant in self.descendants(index) {
+            self.dirty.insert(descendant);
+        }
+        let children: Vec<_> = self.g.neighbors_directed(index, Outgoing).collect();
+        let node = self.g.remove_node(index).unwrap();
+        self.cached = false;
+        self.handles.remove(&node.handle);
+        self.results.remove(&index);
+        self.hashes.remove(&index);
+        self.incomplete.remove(&index);
+        self.dirty.remove(&index);
+        self.last_used.remove(&index);
+        if last != index {
+            self.handles.insert(self.g[index].handle, index);
+            if let Some(result) = self.results.remove(&last) {
+                self.results.insert(index, result);
+            }
+            if let Some(hash) = self.hashes.remove(&last) {
+                self.hashes.insert(index, hash);
+            }
+            if self.incomplete.remove(&last) {
+                self.incomplete.insert(index);
+            }
+            if self.dirty.remove(&last) {
+                self.dirty.insert(index);
+            }
+            if let Some(used) = self.last_used.remove(&last) {
+                self.last_used.insert(index, used);
+            }
+        }
+        for child in children {
+            self.update_complete(moved(child));
+        }
+        Ok(node)
+    }
+
+    /// Returns the current index of the node with the given handle, or an error if the node was removed.
+    pub(crate) fn resolve(&self, handle: NodeHandle) -> Result<NodeIndex, GraphError> {
+        self.index_of(handle).ok_or(GraphError::UnknownHandle(handle))
+    }
+
+    /// Removes the node with the given handle. See [`TextureGraph::remove_node`].
+    pub fn remove_handle(&mut self, handle: NodeHandle) -> Result<Node<T>, GraphError> {
+        self.remove_node(self.resolve(handle)?)
     }
 
     /// Adds an edge between two nodes in the graph, with a given target input.
@@ -81,35 +389,37 @@ impl<T> TextureGraph<T> {
     /// * The target is invalid for the destination node
     /// 
     /// In the case that an edge to the destination already exists with the given target input, the new edges replaces the old.
-    pub fn add_edge(&mut self, src: NodeIndex, dest: NodeIndex, target_input: usize) -> Result<(), String> {
+    pub fn add_edge(&mut self, src: NodeIndex, dest: NodeIndex, target_input: usize) -> Result<(), GraphError> {
         if self.g.node_weight(src).is_none() {
-            return Err(format!("Unknown node {:?}", src));
+            return Err(GraphError::UnknownNode(src));
         }
         if self.g.node_weight(dest).is_none() {
-            return Err(format!("Unknown node {:?}", dest));
+            return Err(GraphError::UnknownNode(dest));
         }
         if src == dest {
-            return Err(format!("Self feeding node {:?}", src))
+            return Err(GraphError::Cycle)
         }
-        let dest_node = &self.g[dest];
-        if target_input >= dest_node.function.inputs() {
-            return Err(format!("Invalid target {} for node {:?}", target_input, dest_node));
+        if target_input >= self.g[dest].function.inputs() {
+            return Err(GraphError::UnexpectedInput { node: dest, input: target_input });
         }
         match self.g.edges_directed(dest, Incoming)
                     .find(|edge| *edge.weight() == target_input) {
             Some(e) => {
-                let old_source = e.source().clone();
-                let old_target = e.target().clone();
-                let old_weight = e.weight().clone();
+                let old_source = e.source();
+                let old_target = e.target();
+                let old_weight = *e.weight();
                 self.g.remove_edge(e.id());
                 let new_edge = self.g.add_edge(src, dest, target_input);
                 if is_cyclic_directed(&self.g) {
                     self.g.add_edge(old_source, old_target, old_weight);
                     self.g.remove_edge(new_edge);
-                    Err(String::from("Edge would create cycle"))
+                    Err(GraphError::Cycle)
                 } else {
-                    self.cached = false;
-                    self.invalidate_nodes(dest);
+                    if self.hashes.get(&dest) != Some(&self.structural_hash(dest)) {
+                        self.cached = false;
+                        self.invalidate_nodes(dest);
+                        self.mark_dirty(dest);
+                    }
                     Ok(())
                 }
             },
@@ -117,33 +427,397 @@ impl<T> TextureGraph<T> {
                 let edge = self.g.add_edge(src, dest, target_input);
                 if is_cyclic_directed(&self.g) {
                     self.g.remove_edge(edge);
-                    Err(String::from("Edge would create cycle"))
+                    Err(GraphError::Cycle)
                 } else {
                     self.cached = false;
+                    self.update_complete(dest);
+                    self.mark_dirty(dest);
                     Ok(())
                 }
             },
         }
     }
 
+    /// Adds an edge between the nodes with the given handles. See [`TextureGraph::add_edge`].
+    pub fn add_edge_handle(&mut self, src: NodeHandle, dest: NodeHandle, target_input: usize) -> Result<(), GraphError> {
+        self.add_edge(self.resolve(src)?, self.resolve(dest)?, target_input)
+    }
+
+    /// Removes the edge connected to the given target input of the destination node, and invalidates the results depending on it.
+    /// Fails if the destination node does not exist, or nothing is connected to the target input.
+    pub fn disconnect_edge(&mut self, dest: NodeIndex, target_input: usize) -> Result<(), GraphError> {
+        if self.g.node_weight(dest).is_none() {
+            return Err(GraphError::UnknownNode(dest));
+        }
+        let edge = match self.g.edges_directed(dest, Incoming).find(|edge| *edge.weight() == target_input) {
+            Some(edge) => edge.id(),
+            None => return Err(GraphError::NotConnected { node: dest, input: target_input }),
+        };
+        self.g.remove_edge(edge);
+        self.cached = false;
+        self.invalidate_nodes(dest);
+        self.update_complete(dest);
+        self.mark_dirty(dest);
+        Ok(())
+    }
+
+    /// Removes the edge to an input of the node with the given handle. See [`TextureGraph::disconnect_edge`].
+    pub fn disconnect_edge_handle(&mut self, dest: NodeHandle, target_input: usize) -> Result<(), GraphError> {
+        self.disconnect_edge(self.resolve(dest)?, target_input)
+    }
+
+    /// Rewires an input of a node to a different source, invalidating the results that depend on the old input.
+    /// Returns the source that was previously connected to the input, if any, so the change can be undone.
+    /// Fails without changing the graph if the edge can not be added, for example because it would create a cycle.
+    pub fn replace_input_subtree(&mut self, dest: NodeIndex, target_input: usize, new_source: NodeIndex) -> Result<Option<NodeIndex>, GraphError> {
+        let old_source = self.input_connections(dest).into_iter()
+            .find(|(target, _)| *target == target_input)
+            .map(|(_, source)| source);
+        self.add_edge(new_source, dest, target_input)?;
+        Ok(old_source)
+    }
+
+    /// Replaces the transformer of a node, for example after its parameters were edited.
+    /// The results of the node and all nodes depending on it are invalidated.
+    /// Fails if the node does not exist in the graph.
+    pub fn set_function(&mut self, index: NodeIndex, function: Box<dyn TextureTransformer<T>>) -> Result<(), GraphError> {
+        match self.g.node_weight_mut(index) {
+            Some(node) => node.function = function,
+            None => return Err(GraphError::UnknownNode(index)),
+        }
+        self.cached = false;
+        self.invalidate_nodes(index);
+        self.update_complete(index);
+        self.mark_dirty(index);
+        Ok(())
+    }
+
+    /// Replaces the transformer of the node with the given handle. See [`TextureGraph::set_function`].
+    pub fn set_function_handle(&mut self, handle: NodeHandle, function: Box<dyn TextureTransformer<T>>) -> Result<(), GraphError> {
+        self.set_function(self.resolve(handle)?, function)
+    }
+
+    /// Sets the output size of every resizable node in the graph, for example when the user changes the global resolution.
+    /// The results of the resized nodes and all nodes depending on them are invalidated.
+    /// Fails without resizing any node if the width or height is zero.
+    pub fn set_resolution(&mut self, width: u32, height: u32) -> Result<(), GraphError> {
+        if width == 0 || height == 0 {
+            return Err(GraphError::InvalidResolution { width, height });
+        }
+        let indices: Vec<NodeIndex> = self.g.node_indices().collect();
+        for index in indices {
+            match self.g[index].function.as_resizable() {
+                Some(function) => function.set_size(width, height),
+                None => continue,
+            }
+            self.cached = false;
+            self.invalidate_nodes(index);
+            self.mark_dirty(index);
+        }
+        Ok(())
+    }
+
+    /// Marks a node and all nodes depending on it as needing to be regenerated by `regenerate_changed`.
+    fn mark_dirty(&mut self, index: NodeIndex) {
+        self.dirty.insert(index);
+        self.dirty.extend(self.descendants(index));
+    }
+
     /// Removes the results of all nodes reachable from the source node.
     /// This can be used when regenerating a node to lazily propogate the regeneration to other nodes.
     pub fn invalidate_nodes(&mut self, source_index: NodeIndex) {
         let mut bfs = Bfs::new(&self.g, source_index);
         while let Some(nx) = bfs.next(&self.g) {
             self.results.remove(&nx);
+            self.hashes.remove(&nx);
+        }
+    }
+
+    /// Removes the results of all nodes, while keeping the nodes and edges.
+    /// This can be used to force a full regeneration of the graph.
+    pub fn clear_results(&mut self) {
+        self.results.clear();
+        self.hashes.clear();
+        self.last_used.clear();
+        self.dirty.extend(self.g.node_indices());
+    }
+
+    /// Adds a copy of a node to the graph, with the same transformer and the same incoming edges.
+    /// The name of the copy is suffixed with " copy".
+    /// Fails if the node does not exist in the graph.
+    pub fn duplicate_node(&mut self, index: NodeIndex) -> Result<NodeIndex, GraphError> {
+        let node = match self.g.node_weight(index) {
+            Some(node) => node,
+            None => return Err(GraphError::UnknownNode(index)),
+        };
+        let mut copy = node.clone();
+        copy.name.push_str(" copy");
+        let inputs: Vec<_> = self.g.edges_directed(index, Incoming)
+            .map(|e| (e.source(), *e.weight()))
+            .collect();
+        let copy_index = self.add_node(copy);
+        for (source, target) in inputs {
+            self.g.add_edge(source, copy_index, target);
+        }
+        self.update_complete(copy_index);
+        Ok(copy_index)
+    }
+
+    /// Builds a new graph containing copies of the given nodes and the edges between them.
+    /// Edges from or to nodes outside of the selection are dropped, so nodes may be incomplete in the new graph.
+    /// Unknown indices are ignored. See `extract_subgraph_with_map` to find the copied nodes in the new graph.
+    pub fn extract_subgraph(&self, nodes: &[NodeIndex]) -> TextureGraph<T> {
+        self.extract_subgraph_with_map(nodes).0
+    }
+
+    /// Builds a new graph like `extract_subgraph`, along with a map from the indices of the copied nodes in this graph
+    /// to their indices in the new graph.
+    pub fn extract_subgraph_with_map(&self, nodes: &[NodeIndex]) -> (TextureGraph<T>, HashMap<NodeIndex, NodeIndex>) {
+        let mut subgraph = TextureGraph::new();
+        let mut mapping = HashMap::new();
+        for &index in nodes {
+            if let Some(node) = self.g.node_weight(index) {
+                mapping.entry(index).or_insert_with(|| subgraph.add_node(node.clone()));
+            }
+        }
+        for edge in self.g.edge_references() {
+            if let (Some(&src), Some(&dest)) = (mapping.get(&edge.source()), mapping.get(&edge.target())) {
+                subgraph.g.add_edge(src, dest, *edge.weight());
+            }
+        }
+        for &index in mapping.values() {
+            subgraph.update_complete(index);
+        }
+        (subgraph, mapping)
+    }
+
+    /// Moves all nodes and edges of another graph into this graph, for example to insert a saved pipeline.
+    /// No edges are created between the two graphs, and the results of the other graph are not kept.
+    /// Returns a map from the indices in the other graph to the new indices in this graph.
+    pub fn merge(&mut self, other: TextureGraph<T>) -> HashMap<NodeIndex, NodeIndex> {
+        let (nodes, edges) = other.g.into_nodes_edges();
+        let mapping: HashMap<NodeIndex, NodeIndex> = nodes.into_iter()
+            .enumerate()
+            .map(|(i, node)| (NodeIndex::new(i), self.add_node(node.weight)))
+            .collect();
+        for edge in edges {
+            self.g.add_edge(mapping[&edge.source()], mapping[&edge.target()], edge.weight);
+        }
+        for &index in mapping.values() {
+            self.update_complete(index);
+        }
+        mapping
+    }
+
+    /// Computes a hash for every node from its parameters and the hashes of its inputs.
+    /// Two generations of a node with the same structural hash produce the same result.
+    pub fn structural_hashes(&self) -> HashMap<NodeIndex, u64> {
+        let mut hashes = HashMap::new();
+        let mut topo = Topo::new(&self.g);
+        while let Some(index) = topo.next(&self.g) {
+            let hash = self.node_hash(index, &hashes);
+            hashes.insert(index, hash);
+        }
+        hashes
+    }
+
+    /// Computes the structural hash of a single node like `structural_hashes`, hashing only the nodes that feed into it.
+    pub fn structural_hash(&self, index: NodeIndex) -> u64 {
+        let mut hashes = HashMap::new();
+        let mut stack = vec![index];
+        while let Some(&current) = stack.last() {
+            if hashes.contains_key(&current) {
+                stack.pop();
+                continue
+            }
+            let missing: Vec<_> = self.g.neighbors_directed(current, Incoming)
+                .filter(|source| !hashes.contains_key(source))
+                .collect();
+            if missing.is_empty() {
+                stack.pop();
+                let hash = self.node_hash(current, &hashes);
+                hashes.insert(current, hash);
+            } else {
+                stack.extend(missing);
+            }
+        }
+        hashes[&index]
+    }
+
+    /// Computes the structural hash of a single node, given the hashes of its inputs.
+    fn node_hash(&self, index: NodeIndex, hashes: &HashMap<NodeIndex, u64>) -> u64 {
+        let mut inputs: Vec<_> = self.g.edges_directed(index, Incoming)
+            .map(|e| (*e.weight(), hashes.get(&e.source()).copied()))
+            .collect();
+        inputs.sort();
+        let mut hasher = DefaultHasher::new();
+        self.g[index].handle.hash(&mut hasher);
+        self.g[index].function.params_hash().hash(&mut hasher);
+        inputs.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the target input and source node of every edge feeding into the given node, sorted by target input.
+    pub fn input_connections(&self, index: NodeIndex) -> Vec<(usize, NodeIndex)> {
+        let mut inputs: Vec<_> = self.g.edges_directed(index, Incoming)
+            .map(|e| (*e.weight(), e.source()))
+            .collect();
+        inputs.sort_by_key(|(target, _)| *target);
+        inputs
+    }
+
+    /// Returns all nodes that feed into the given node, directly or indirectly.
+    pub fn ancestors(&self, index: NodeIndex) -> Vec<NodeIndex> {
+        let reversed = Reversed(&self.g);
+        let mut bfs = Bfs::new(reversed, index);
+        let mut ancestors = Vec::new();
+        while let Some(nx) = bfs.next(reversed) {
+            if nx != index {
+                ancestors.push(nx);
+            }
+        }
+        ancestors
+    }
+
+    /// Returns all nodes that are fed by the given node, directly or indirectly.
+    pub fn descendants(&self, index: NodeIndex) -> Vec<NodeIndex> {
+        let mut bfs = Bfs::new(&self.g, index);
+        let mut descendants = Vec::new();
+        while let Some(nx) = bfs.next(&self.g) {
+            if nx != index {
+                descendants.push(nx);
+            }
+        }
+        descendants
+    }
+
+    /// Collects metrics of the graph, for example for a status bar.
+    pub fn stats(&self) -> GraphStats {
+        let mut chain_lengths: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut topo = Topo::new(&self.g);
+        while let Some(index) = topo.next(&self.g) {
+            let longest_input = self.g.neighbors_directed(index, Incoming).map(|source| chain_lengths[&source]).max().unwrap_or(0);
+            chain_lengths.insert(index, longest_input + 1);
         }
+        GraphStats {
+            node_count: self.g.node_count(),
+            edge_count: self.g.edge_count(),
+            complete_nodes: self.g.node_count() - self.incomplete.len(),
+            cached_results: self.results.len(),
+            longest_chain: chain_lengths.values().copied().max().unwrap_or(0),
+            output_nodes: self.g.externals(Outgoing).count(),
+        }
+    }
+
+    /// Returns the statistics a node reports about its cached result, see `TextureTransformer::probe`.
+    /// Returns None if the node does not report statistics, or has no cached result.
+    pub fn probe_stats(&self, index: NodeIndex) -> Option<ChannelStats> {
+        let result = self.results.get(&index)?;
+        self.g[index].function.probe(result)
+    }
+
+    /// Collects the statistics of every node that reports them about its cached result.
+    pub fn all_probe_stats(&self) -> HashMap<NodeIndex, ChannelStats> {
+        self.results.iter()
+            .filter_map(|(&index, result)| Some((index, self.g[index].function.probe(result)?)))
+            .collect()
+    }
+
+    /// Returns all nodes with no outgoing edges, which are the final textures of the graph.
+    pub fn output_nodes(&self) -> Vec<NodeIndex> {
+        self.g.externals(Outgoing).collect()
     }
 
-    /// Checks if all targets of a given node are connected by edges..
+    /// Returns all nodes whose results never contribute to an output of the graph.
+    /// An output is a node with no outgoing edges that is fed by at least one other node,
+    /// so nodes that are not connected to anything are reported as unused.
+    pub fn unused_nodes(&self) -> Vec<NodeIndex> {
+        let mut used = HashSet::new();
+        for index in self.output_nodes() {
+            if self.g.neighbors_directed(index, Incoming).next().is_some() {
+                used.insert(index);
+                used.extend(self.ancestors(index));
+            }
+        }
+        self.g.node_indices().filter(|index| !used.contains(index)).collect()
+    }
+
+    /// Checks if every target of a given node is connected by exactly one edge, and no edges connect to other targets.
     pub fn node_complete(&self, node_index: NodeIndex) -> bool {
         let node = &self.g[node_index];
-        node.function.inputs() == self.g.neighbors_directed(node_index, Incoming).count()
+        let mut targets: Vec<_> = self.g.edges_directed(node_index, Incoming).map(|e| *e.weight()).collect();
+        targets.sort();
+        targets.into_iter().eq(0..node.function.inputs())
+    }
+
+    /// Checks the whole graph for problems before generating it, so all of them can be shown at once.
+    /// Reports every missing input, every edge into an input a node does not have, inputs of mismatched sizes, and any cycle.
+    pub fn validate(&self) -> Result<(), Vec<GraphError>> {
+        let mut errors = Vec::new();
+        if is_cyclic_directed(&self.g) {
+            errors.push(GraphError::Cycle);
+        } else {
+            errors.extend(self.size_inference().1);
+        }
+        for node in self.g.node_indices() {
+            let inputs = self.g[node].function.inputs();
+            let targets: HashSet<usize> = self.g.edges_directed(node, Incoming).map(|e| *e.weight()).collect();
+            let mut unexpected: Vec<_> = targets.iter().copied().filter(|&input| input >= inputs).collect();
+            unexpected.sort();
+            errors.extend((0..inputs).filter(|input| !targets.contains(input)).map(|input| GraphError::MissingInput { node, input }));
+            errors.extend(unexpected.into_iter().map(|input| GraphError::UnexpectedInput { node, input }));
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Predicts the output size of every node from the sizes generators report, without generating anything.
+    /// Nodes whose size can not be predicted, for example because an input is missing or has a mismatched size, are left out.
+    pub fn infer_sizes(&self) -> HashMap<NodeIndex, (u32, u32)> {
+        self.size_inference().0
+    }
+
+    /// Predicts the output sizes in topological order, and collects the nodes that do not accept the sizes of their inputs.
+    fn size_inference(&self) -> (HashMap<NodeIndex, (u32, u32)>, Vec<GraphError>) {
+        let mut sizes = HashMap::new();
+        let mut errors = Vec::new();
+        let mut topo = Topo::new(&self.g);
+        while let Some(index) = topo.next(&self.g) {
+            let function = &self.g[index].function;
+            let input_sizes: Option<Vec<(u32, u32)>> = self.input_connections(index).into_iter()
+                .map(|(_, source)| sizes.get(&source).copied())
+                .collect();
+            let input_sizes = match input_sizes {
+                Some(input_sizes) if input_sizes.len() == function.inputs() => input_sizes,
+                _ => continue,
+            };
+            match function.output_size(&input_sizes) {
+                Some(size) => { sizes.insert(index, size); },
+                None if !input_sizes.is_empty() => errors.push(GraphError::SizeMismatch { node: index, sizes: input_sizes }),
+                None => (),
+            }
+        }
+        (sizes, errors)
     }
 
     /// Checks if all targets of all nodes are connected by edges.
     pub fn graph_complete(&self) -> bool {
-        self.g.node_indices().all(|i| self.node_complete(i))
+        self.incomplete.is_empty()
+    }
+
+    /// Returns all nodes that are missing incoming edges, in index order.
+    pub fn incomplete_nodes(&self) -> Vec<NodeIndex> {
+        let mut incomplete: Vec<_> = self.incomplete.iter().copied().collect();
+        incomplete.sort();
+        incomplete
+    }
+
+    /// Updates the cached completeness of a node after its incoming edges changed.
+    fn update_complete(&mut self, index: NodeIndex) {
+        if self.node_complete(index) {
+            self.incomplete.remove(&index);
+        } else {
+            self.incomplete.insert(index);
+        }
     }
 
     /// Returns the generated value of a given node index.
@@ -154,111 +828,293 @@ impl<T> TextureGraph<T> {
         }
     }
 
-    /// Generates the value of a given node.
+    /// Returns every node in an order in which they can be generated, with every node after the nodes feeding into it.
+    /// Together with [`TextureGraph::eval_one`], this lets callers drive generation themselves,
+    /// for example to spread it over several frames, stop early, or store results elsewhere.
+    /// Nodes that are part of a cycle are left out.
+    pub fn eval_order(&self) -> Vec<NodeIndex> {
+        let mut order = Vec::new();
+        let mut topo = Topo::new(&self.g);
+        while let Some(index) = topo.next(&self.g) {
+            order.push(index);
+        }
+        order
+    }
+
+    /// Generates the value of a single node from the results of the nodes feeding into it.
+    /// Callers must generate nodes in the order of [`TextureGraph::eval_order`], so the inputs of a node are generated before it.
+    /// Unlike the `generate_graph` functions, results are not evicted afterwards, even if there is a limit on cached results.
     /// Fails if:
+    /// * The node does not exist
     /// * Node is not connected by enough targets
     /// * An input of an input node is not generated
     /// * Inputs of the node function are not valid.
-    pub fn generate_node(&mut self, index: NodeIndex) -> Result<(), String> {
-        if !self.node_complete(index) {
-            return Err(String::from("Node not completed"));
+    pub fn eval_one(&mut self, index: NodeIndex) -> Result<(), GraphError> {
+        if self.g.node_weight(index).is_none() {
+            return Err(GraphError::UnknownNode(index));
         }
-        let mut inputs: Vec<_> = self.g.edges_directed(index, Incoming)
-            .map(|e| {
-                let source = e.source();
-                let target = e.weight();
-                (*target, source)
-            }).collect();
-        inputs.sort_by_key(|(t, _)| *t);
-        let targets: Vec<_> = inputs.iter().map(|(_, source)| source).collect();
-        if targets.iter().any(|src| !self.results.contains_key(src)) {
-            return Err(String::from("Predecessors of node not generated"))
-        }
-        let targets = targets.iter().map(|&target| &self.results[target]).collect();
+        let inputs = self.input_connections(index);
+        let count = self.g[index].function.inputs();
+        if let Some(input) = (0..count).find(|&input| inputs.iter().all(|(target, _)| *target != input)) {
+            return Err(GraphError::MissingInput { node: index, input });
+        }
+        if let Some((input, _)) = inputs.iter().find(|(target, _)| *target >= count) {
+            return Err(GraphError::UnexpectedInput { node: index, input: *input });
+        }
+        if let Some((input, _)) = inputs.iter().find(|(_, source)| !self.results.contains_key(source)) {
+            return Err(GraphError::InputNotGenerated { node: index, input: *input });
+        }
+        for (_, source) in &inputs {
+            self.use_clock += 1;
+            self.last_used.insert(*source, self.use_clock);
+        }
+        let targets = inputs.iter().map(|(_, source)| &self.results[source]).collect();
         let node = &self.g[index];
         if !node.function.is_valid(&targets) {
-            return Err(String::from("Input images not valid"))
+            return Err(GraphError::InvalidInputs { node: index });
         }
         let generated_value = node.function.generate(targets);
         self.results.insert(index, generated_value);
+        let hash = self.node_hash(index, &self.hashes);
+        self.hashes.insert(index, hash);
+        self.dirty.remove(&index);
+        self.use_clock += 1;
+        self.last_used.insert(index, self.use_clock);
         Ok(())
     }
+
+    /// Generates the value of a given node.
+    /// Fails for the same reasons as [`TextureGraph::eval_one`].
+    pub fn generate_node(&mut self, index: NodeIndex) -> Result<(), GraphError> {
+        self.eval_one(index)
+    }
+
+    /// Generates the value of the node with the given handle. See [`TextureGraph::generate_node`].
+    pub fn generate_handle(&mut self, handle: NodeHandle) -> Result<(), GraphError> {
+        self.generate_node(self.resolve(handle)?)
+    }
+
+    /// Generates the value of a given node, and returns a reference to the generated value.
+    /// Fails for the same reasons as [`TextureGraph::generate_node`].
+    pub fn generate_node_ref(&mut self, index: NodeIndex) -> Result<&T, GraphError> {
+        self.eval_one(index)?;
+        Ok(&self.results[&index])
+    }
     
     /// Generates the entire graph in a topological order.
     /// This function does not skip any previously generated nodes.
-    pub fn generate_graph(&mut self) -> Result<(), String> {
+    pub fn generate_graph(&mut self) -> Result<(), GraphError> {
         let mut topo = Topo::new(&self.g);
         while let Some(index) = topo.next(&self.g) {
-            match self.generate_node(index) {
-                Ok(_) => continue,
-                Err(msg) => return Err(msg),
+            if let Err(e) = self.generate_node(index) {
+                self.evict_results();
+                return Err(e);
+            }
+        }
+        self.evict_results();
+        Ok(())
+    }
+
+    /// Generates the entire graph in a topological order like `generate_graph`, but stops when the flag is set,
+    /// for example by the user interface after the graph was edited. The flag is checked before every node,
+    /// so a node that is being generated is finished first. The results generated until then are kept.
+    pub fn generate_graph_cancellable(&mut self, cancel: &AtomicBool) -> Result<(), GraphError> {
+        for index in self.eval_order() {
+            let result = match cancel.load(Ordering::Relaxed) {
+                true => Err(GraphError::Cancelled),
+                false => self.eval_one(index),
+            };
+            if let Err(e) = result {
+                self.evict_results();
+                return Err(e);
             }
         }
+        self.evict_results();
         Ok(())
     }
 
+    /// Generates the entire graph in a topological order, continuing after nodes that fail to generate.
+    /// Nodes that depend on a failed node are skipped, as they can not be generated either.
+    /// Returns the error of every node that failed, in the order they were attempted.
+    pub fn generate_graph_report(&mut self) -> Vec<(NodeIndex, GraphError)> {
+        let mut failed = HashSet::new();
+        let mut errors = Vec::new();
+        let mut topo = Topo::new(&self.g);
+        while let Some(index) = topo.next(&self.g) {
+            if self.g.neighbors_directed(index, Incoming).any(|source| failed.contains(&source)) {
+                failed.insert(index);
+                continue
+            }
+            if let Err(e) = self.eval_one(index) {
+                failed.insert(index);
+                errors.push((index, e));
+            }
+        }
+        self.evict_results();
+        errors
+    }
+
     /// Generates the entire graph in a topological order.
-    /// This function skips any previously generated nodes.
-    pub fn generate_graph_missing(&mut self) -> Result<(), String> {
+    /// This function skips any previously generated nodes whose structure has not changed since.
+    pub fn generate_graph_missing(&mut self) -> Result<(), GraphError> {
+        let hashes = self.structural_hashes();
         let mut topo = Topo::new(&self.g);
         while let Some(index) = topo.next(&self.g) {
-            if self.results.contains_key(&index) {
+            if self.results.contains_key(&index) && self.hashes.get(&index) == Some(&hashes[&index]) {
                 continue
             }
-            match self.generate_node(index) {
-                Ok(_) => continue,
-                Err(msg) => return Err(msg),
+            if let Err(e) = self.generate_node(index) {
+                self.evict_results();
+                return Err(e);
             }
         }
+        self.evict_results();
         Ok(())
-    }    
+    }
+
+    /// Generates a node if it has no up to date result, along with any of its inputs whose results are missing.
+    /// Fails for the same reasons as [`TextureGraph::generate_node`].
+    pub fn generate_target(&mut self, index: NodeIndex) -> Result<(), GraphError> {
+        if self.g.node_weight(index).is_none() {
+            return Err(GraphError::UnknownNode(index));
+        }
+        let result = if self.results.contains_key(&index) && !self.dirty.contains(&index) {
+            self.use_clock += 1;
+            self.last_used.insert(index, self.use_clock);
+            Ok(())
+        } else {
+            self.generate_with_missing_inputs(index)
+        };
+        self.evict_results();
+        result
+    }
+
+    /// Generates a node, after generating the nodes feeding into it whose results are missing, in a topological order.
+    fn generate_with_missing_inputs(&mut self, index: NodeIndex) -> Result<(), GraphError> {
+        let mut missing = HashSet::from([index]);
+        let mut stack = vec![index];
+        while let Some(current) = stack.pop() {
+            for source in self.g.neighbors_directed(current, Incoming) {
+                if !self.results.contains_key(&source) && missing.insert(source) {
+                    stack.push(source);
+                }
+            }
+        }
+        let mut topo = Topo::new(&self.g);
+        while let Some(nx) = topo.next(&self.g) {
+            if missing.contains(&nx) {
+                self.eval_one(nx)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the least recently used results until the amount of results is within the limit.
+    /// Results of output nodes are never removed.
+    fn evict_results(&mut self) {
+        let max = match self.max_cached_results {
+            Some(max) => max,
+            None => return,
+        };
+        let outputs: HashSet<_> = self.output_nodes().into_iter().collect();
+        let mut candidates: Vec<_> = self.results.keys()
+            .filter(|index| !outputs.contains(index))
+            .map(|&index| (self.last_used.get(&index).copied().unwrap_or(0), index))
+            .collect();
+        candidates.sort();
+        for (_, index) in candidates {
+            if self.results.len() <= max {
+                break
+            }
+            self.results.remove(&index);
+            self.hashes.remove(&index);
+            self.last_used.remove(&index);
+        }
+    }
+
+    /// Regenerates the nodes that were edited since they were last generated, and the nodes depending on them, in a topological order.
+    /// Unlike `generate_graph_missing`, this also regenerates nodes that still have a result when one of their inputs was edited.
+    /// Fails for the same reasons as [`TextureGraph::generate_node`], in which case the nodes that were not regenerated stay marked.
+    pub fn regenerate_changed(&mut self) -> Result<(), GraphError> {
+        let mut topo = Topo::new(&self.g);
+        while let Some(index) = topo.next(&self.g) {
+            if self.dirty.contains(&index) {
+                if let Err(e) = self.generate_with_missing_inputs(index) {
+                    self.evict_results();
+                    return Err(e);
+                }
+            }
+        }
+        self.evict_results();
+        Ok(())
+    }
 
     /// Returns the generated value of a given index if it exists.
     pub fn get_generated_node(&mut self, index: &NodeIndex) -> Option<&T> {
+        if self.results.contains_key(index) {
+            self.use_clock += 1;
+            self.last_used.insert(*index, self.use_clock);
+        }
         self.results.get(index)
     }
+
+    /// Returns the generated value of the node with the given handle if it exists.
+    pub fn get_generated_handle(&mut self, handle: NodeHandle) -> Option<&T> {
+        let index = self.index_of(handle)?;
+        self.get_generated_node(&index)
+    }
+
+    /// Returns a Graphviz representation of the graph, with nodes labeled by their name
+    /// and edges labeled by the target input they connect to.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for index in self.g.node_indices() {
+            let name = self.g[index].name.replace('\\', "\\\\").replace('"', "\\\"");
+            dot.push_str(&format!("    {} [label=\"{}\"];\n", index.index(), name));
+        }
+        for edge in self.g.edge_references() {
+            dot.push_str(&format!("    {} -> {} [label=\"{}\"];\n", edge.source().index(), edge.target().index(), edge.weight()));
+        }
+        dot.push('}');
+        dot
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use petgraph::algo::is_cyclic_directed;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-    use crate::{TextureGraph, Node, TextureTransformer};
+    use petgraph::algo::is_cyclic_directed;
 
-    struct Add{}
-    impl TextureTransformer<i32> for Add {
-        fn generate(&self, inputs: Vec<&i32>) -> i32 {
-            inputs.iter().copied().sum()
-        }
+    use crate::{ChannelStats, GraphError, GraphStats, TextureGraph, Node, NodeIndex, Resizable, TextureTransformer};
+    use crate::fixtures::{Add, Const, Double};
 
-        fn inputs(&self) -> usize {
-            2
-        }
-    }
-
-    struct Const(i32);
-    impl TextureTransformer<i32> for Const {
+    #[derive(Clone)]
+    struct Area(u32, u32);
+    impl TextureTransformer<i32> for Area {
         fn generate(&self, _inputs: Vec<&i32>) -> i32 {
-            self.0
+            (self.0 * self.1) as i32
         }
 
         fn inputs(&self) -> usize {
             0
         }
-    }
 
-    struct Double{}
-    impl TextureTransformer<i32> for Double {
-        fn generate(&self, inputs: Vec<&i32>) -> i32 {
-            inputs[0] * 2
+        fn params_hash(&self) -> u64 {
+            (self.0 as u64) << 32 | self.1 as u64
         }
 
-        fn inputs(&self) -> usize {
-            1
+        fn as_resizable(&mut self) -> Option<&mut dyn Resizable> {
+            Some(self)
         }
+    }
 
-        fn is_valid(&self, inputs: &Vec<&i32>) -> bool {
-            inputs.len() > 0 && *inputs[0] >= 0
+    impl Resizable for Area {
+        fn set_size(&mut self, width: u32, height: u32) {
+            self.0 = width;
+            self.1 = height;
         }
     }
 
@@ -274,6 +1130,14 @@ mod tests {
         assert_eq!(String::from("Test node"), node.name);
     }
 
+    #[test]
+    fn clone_node() {
+        let node = Node::new(String::from("Test node"), Box::new(Const(4)));
+        let copy = node.clone();
+        assert_eq!(node.name, copy.name);
+        assert_eq!(node.function.generate(vec![]), copy.function.generate(vec![]));
+    }
+
     #[test]
     fn add_node() {
         let mut graph = TextureGraph::<i32>::new();
@@ -305,6 +1169,45 @@ mod tests {
         assert!(graph.add_edge(index1, index2, 1).is_ok());
         assert_eq!(2, graph.g.edge_count());
     }
+
+    #[test]
+    fn edge_count() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Add{})));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Add{})));
+        assert_eq!(0, graph.edge_count());
+        assert!(graph.add_edge(index1, index2, 0).is_ok());
+        assert_eq!(1, graph.edge_count());
+        assert!(graph.add_edge(index1, index2, 1).is_ok());
+        assert_eq!(2, graph.edge_count());
+    }
+
+    #[test]
+    fn input_connections() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        assert!(graph.input_connections(index3).is_empty());
+        graph.add_edge(index2, index3, 1).unwrap();
+        graph.add_edge(index1, index3, 0).unwrap();
+        assert_eq!(vec![(0, index1), (1, index2)], graph.input_connections(index3));
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_eq!(vec![(0, index2), (1, index2)], graph.input_connections(index3));
+        assert!(graph.input_connections(index1).is_empty());
+    }
+
+    #[test]
+    fn contains_node() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Add{})));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Add{})));
+        assert!(graph.contains_node(index1));
+        assert!(graph.contains_node(index2));
+        graph.g.remove_node(index2);
+        assert!(graph.contains_node(index1));
+        assert!(!graph.contains_node(index2));
+    }
     
 
     #[test]
@@ -340,7 +1243,7 @@ mod tests {
         let mut graph = TextureGraph::<i32>::new();
         let node = Node::new(String::from("N"), Box::new(Add{}));
         let index = graph.add_node(node);
-        assert!(graph.add_edge(index, index, 0).is_err());
+        assert_eq!(Err(GraphError::Cycle), graph.add_edge(index, index, 0));
     }
 
     #[test]
@@ -351,7 +1254,7 @@ mod tests {
         let index1 = graph.add_node(node1);
         let index2 = graph.add_node(node2);
         graph.g.remove_node(index2);
-        assert!(graph.add_edge(index1, index2, 0).is_err());
+        assert_eq!(Err(GraphError::UnknownNode(index2)), graph.add_edge(index1, index2, 0));
         assert_eq!(0, graph.g.edge_count());
         assert!(graph.add_edge(index2, index1, 0).is_err());
         assert_eq!(0, graph.g.edge_count());
@@ -372,7 +1275,7 @@ mod tests {
         assert_eq!(1, graph.g.edge_count());
         assert!(!is_cyclic_directed(&graph.g));
         assert!(graph.add_edge(index2, index3, 0).is_ok());
-        assert!(graph.add_edge(index3, index1, 0).is_err());
+        assert_eq!(Err(GraphError::Cycle), graph.add_edge(index3, index1, 0));
         assert_eq!(2, graph.g.edge_count());
     }
 
@@ -406,8 +1309,8 @@ mod tests {
         let node2 = Node::new(String::from("N2"), Box::new(Const(3)));
         let index1 = graph.add_node(node1);
         let index2 = graph.add_node(node2);
-        assert!(graph.add_edge(index1, index2, 0).is_err());
-        assert!(graph.add_edge(index2, index1, 2).is_err());
+        assert_eq!(Err(GraphError::UnexpectedInput { node: index2, input: 0 }), graph.add_edge(index1, index2, 0));
+        assert_eq!(Err(GraphError::UnexpectedInput { node: index1, input: 2 }), graph.add_edge(index2, index1, 2));
     }
 
     #[test]
@@ -432,6 +1335,417 @@ mod tests {
         assert!(graph.graph_complete());
     }
 
+    #[derive(Clone)]
+    struct Counted(i32, Arc<AtomicUsize>);
+    impl TextureTransformer<i32> for Counted {
+        fn generate(&self, inputs: Vec<&i32>) -> i32 {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            self.0 + inputs.into_iter().sum::<i32>()
+        }
+
+        fn inputs(&self) -> usize {
+            1
+        }
+
+        fn params_hash(&self) -> u64 {
+            self.0 as u64
+        }
+    }
+
+    #[test]
+    fn regenerate_changed() {
+        let mut graph = TextureGraph::<i32>::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Counted(10, count.clone()))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Counted(100, count.clone()))));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Counted(1000, count.clone()))));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        graph.add_edge(index1, index4, 0).unwrap();
+        graph.regenerate_changed().unwrap();
+        assert_eq!(3, count.load(Ordering::SeqCst));
+        assert_eq!(Some(&111), graph.get_generated_node(&index3));
+        graph.regenerate_changed().unwrap();
+        assert_eq!(3, count.load(Ordering::SeqCst));
+        // Editing N2 regenerates N2 and N3, but not N4 which does not depend on it.
+        graph.set_function(index2, Box::new(Counted(20, count.clone()))).unwrap();
+        graph.regenerate_changed().unwrap();
+        assert_eq!(5, count.load(Ordering::SeqCst));
+        assert_eq!(Some(&121), graph.get_generated_node(&index3));
+        assert_eq!(Some(&1001), graph.get_generated_node(&index4));
+        assert!(graph.set_function(NodeIndex::new(10), Box::new(Const(1))).is_err());
+        let index5 = graph.add_node(Node::new(String::from("N5"), Box::new(Double{})));
+        assert_eq!(Err(GraphError::MissingInput { node: index5, input: 0 }), graph.regenerate_changed());
+    }
+
+    #[test]
+    fn max_cached_results() {
+        let mut graph = TextureGraph::<i32>::new();
+        let mut indices = vec![graph.add_node(Node::new(String::from("N0"), Box::new(Const(1))))];
+        for i in 1..5 {
+            let index = graph.add_node(Node::new(format!("N{}", i), Box::new(Double{})));
+            graph.add_edge(indices[i - 1], index, 0).unwrap();
+            indices.push(index);
+        }
+        graph.set_max_cached_results(Some(2));
+        graph.generate_graph().unwrap();
+        let cached: Vec<_> = indices.iter().filter(|index| graph.results.contains_key(index)).copied().collect();
+        assert_eq!(vec![indices[3], indices[4]], cached);
+        assert_eq!(Some(&16), graph.get_generated_node(&indices[4]));
+        // An evicted result is generated again on demand, along with the evicted results feeding into it.
+        graph.generate_target(indices[2]).unwrap();
+        assert_eq!(Some(&4), graph.get_generated_node(&indices[2]));
+        assert_eq!(2, graph.results.len());
+        assert!(graph.results.contains_key(&indices[4]));
+        graph.set_max_cached_results(None);
+        graph.generate_graph_missing().unwrap();
+        assert_eq!(5, graph.results.len());
+    }
+
+    #[test]
+    fn max_cached_results_after_error() {
+        let mut graph = TextureGraph::<i32>::new();
+        let mut indices = vec![graph.add_node(Node::new(String::from("N0"), Box::new(Const(1))))];
+        for i in 1..4 {
+            let index = graph.add_node(Node::new(format!("N{}", i), Box::new(Double{})));
+            graph.add_edge(indices[i - 1], index, 0).unwrap();
+            indices.push(index);
+        }
+        // The second input of the sum is missing, so every run fails after generating the chain before it.
+        let sum = graph.add_node(Node::new(String::from("Sum"), Box::new(Add{})));
+        graph.add_edge(indices[3], sum, 0).unwrap();
+        graph.set_max_cached_results(Some(2));
+        assert!(graph.generate_graph().is_err());
+        assert_eq!(2, graph.results.len());
+        graph.clear_results();
+        assert!(graph.generate_graph_cancellable(&AtomicBool::new(false)).is_err());
+        assert_eq!(2, graph.results.len());
+        graph.clear_results();
+        assert!(graph.generate_graph_missing().is_err());
+        assert_eq!(2, graph.results.len());
+        graph.clear_results();
+        assert!(graph.generate_target(sum).is_err());
+        assert_eq!(2, graph.results.len());
+    }
+
+    #[test]
+    fn remove_node_keeps_handles() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Double{})));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        graph.add_edge(index3, index4, 0).unwrap();
+        graph.generate_graph().unwrap();
+        let handles: Vec<_> = [index1, index2, index3, index4].iter().map(|&i| graph.handle_of(i).unwrap()).collect();
+        let removed = graph.remove_node(index2).unwrap();
+        assert_eq!("N2", removed.name);
+        assert_eq!(3, graph.node_count());
+        assert_eq!(2, graph.edge_count());
+        assert!(graph.index_of(handles[1]).is_none());
+        // The last node took over the index of the removed node, but its handle still refers to it.
+        let moved = graph.index_of(handles[3]).unwrap();
+        assert_eq!(index2, moved);
+        assert_eq!("N4", graph.get_node(moved).unwrap().name);
+        assert_eq!(Some(index1), graph.index_of(handles[0]));
+        assert_eq!(Some(index3), graph.index_of(handles[2]));
+        assert!(!graph.node_complete(index3));
+        assert_eq!(vec![index3], graph.incomplete_nodes());
+        assert!(graph.get_generated_node(&index3).is_none());
+        assert!(graph.get_generated_node(&moved).is_none());
+        assert_eq!(Some(&1), graph.get_generated_node(&index1));
+        let const2 = graph.add_node(Node::new(String::from("N5"), Box::new(Const(5))));
+        graph.add_edge(const2, index3, 1).unwrap();
+        graph.regenerate_changed().unwrap();
+        assert_eq!(Some(&12), graph.get_generated_node(&moved));
+        let const2_handle = graph.handle_of(const2).unwrap();
+        assert_eq!(GraphError::UnknownHandle(handles[1]), graph.remove_handle(handles[1]).unwrap_err());
+        assert!(graph.remove_handle(handles[0]).is_ok());
+        assert_eq!(Some(index1), graph.index_of(const2_handle));
+    }
+
+    #[test]
+    fn handle_variants() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        let [handle1, handle2, handle3] = [index1, index2, index3].map(|index| graph.handle_of(index).unwrap());
+        graph.add_edge_handle(handle2, handle3, 0).unwrap();
+        // Removing the first node moves the last node to its index, the handles keep referring to the same nodes.
+        graph.remove_handle(handle1).unwrap();
+        assert_eq!(Err(GraphError::UnknownHandle(handle1)), graph.add_edge_handle(handle1, handle3, 0));
+        assert_eq!(Err(GraphError::UnknownHandle(handle1)), graph.generate_handle(handle1));
+        graph.generate_handle(handle2).unwrap();
+        graph.generate_handle(handle3).unwrap();
+        assert_eq!(Some(&4), graph.get_generated_handle(handle3));
+        assert!(graph.get_generated_handle(handle1).is_none());
+        graph.set_function_handle(handle2, Box::new(Const(5))).unwrap();
+        assert!(graph.get_generated_handle(handle3).is_none());
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&10), graph.get_generated_handle(handle3));
+        graph.disconnect_edge_handle(handle3, 0).unwrap();
+        assert_eq!(0, graph.edge_count());
+        assert!(graph.disconnect_edge_handle(handle3, 0).is_err());
+    }
+
+    #[test]
+    fn replace_input_subtree() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(5))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Double{})));
+        assert_eq!(Ok(None), graph.replace_input_subtree(index3, 0, index1));
+        graph.add_edge(index3, index4, 0).unwrap();
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&4), graph.get_generated_node(&index4));
+        assert_eq!(Ok(Some(index1)), graph.replace_input_subtree(index3, 0, index2));
+        assert!(graph.get_generated_node(&index3).is_none());
+        assert!(graph.get_generated_node(&index4).is_none());
+        assert!(graph.replace_input_subtree(index3, 0, index4).is_err());
+        assert_eq!(vec![(0, index2)], graph.input_connections(index3));
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&20), graph.get_generated_node(&index4));
+    }
+
+    #[derive(Clone)]
+    struct Probe{}
+    impl TextureTransformer<i32> for Probe {
+        fn generate(&self, inputs: Vec<&i32>) -> i32 {
+            *inputs[0]
+        }
+
+        fn inputs(&self) -> usize {
+            1
+        }
+
+        fn params_hash(&self) -> u64 {
+            0
+        }
+
+        fn probe(&self, output: &i32) -> Option<ChannelStats> {
+            let value = vec![*output as f32];
+            Some(ChannelStats { min: value.clone(), max: value.clone(), mean: value })
+        }
+    }
+
+    #[test]
+    fn probe_stats() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(3))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Probe{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_eq!(None, graph.probe_stats(index2));
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&6), graph.get_generated_node(&index3));
+        let expected = ChannelStats { min: vec![3.0], max: vec![3.0], mean: vec![3.0] };
+        assert_eq!(Some(expected.clone()), graph.probe_stats(index2));
+        assert_eq!(None, graph.probe_stats(index1));
+        let all = graph.all_probe_stats();
+        assert_eq!(1, all.len());
+        assert_eq!(Some(&expected), all.get(&index2));
+    }
+
+    #[test]
+    fn stats() {
+        let mut graph = TextureGraph::<i32>::new();
+        assert_eq!(0, graph.stats().longest_chain);
+        let top = graph.add_node(Node::new(String::from("Top"), Box::new(Const(1))));
+        let left = graph.add_node(Node::new(String::from("Left"), Box::new(Double{})));
+        let right = graph.add_node(Node::new(String::from("Right"), Box::new(Double{})));
+        let bottom = graph.add_node(Node::new(String::from("Bottom"), Box::new(Add{})));
+        let unfinished = graph.add_node(Node::new(String::from("Unfinished"), Box::new(Double{})));
+        graph.add_edge(top, left, 0).unwrap();
+        graph.add_edge(top, right, 0).unwrap();
+        graph.add_edge(left, bottom, 0).unwrap();
+        graph.add_edge(right, bottom, 1).unwrap();
+        graph.generate_target(bottom).unwrap();
+        let expected = GraphStats {
+            node_count: 5,
+            edge_count: 4,
+            complete_nodes: 4,
+            cached_results: 4,
+            longest_chain: 3,
+            output_nodes: 2,
+        };
+        assert_eq!(expected, graph.stats());
+        assert!(graph.get_generated_node(&unfinished).is_none());
+    }
+
+    #[test]
+    fn validate() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Add{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_eq!(Err(vec![GraphError::MissingInput { node: index2, input: 1 }]), graph.validate());
+        graph.add_edge(index1, index2, 1).unwrap();
+        assert_eq!(Ok(()), graph.validate());
+        graph.set_function(index3, Box::new(Const(3))).unwrap();
+        assert_eq!(Err(vec![GraphError::UnexpectedInput { node: index3, input: 0 }]), graph.validate());
+    }
+
+    #[test]
+    fn node_description() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        assert_eq!(Some(String::from("Const")), graph.node_description(index));
+        assert_eq!(None, graph.node_description(NodeIndex::new(5)));
+    }
+
+    #[test]
+    fn input_names() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Add{})));
+        assert_eq!(Some(vec![]), graph.input_names(index1));
+        assert_eq!(vec!["input 0", "input 1"], graph.input_names(index2).unwrap());
+        assert_eq!(Some(1), graph.input_by_name(index2, "input 1"));
+        assert_eq!(None, graph.input_by_name(index2, "input 2"));
+        assert_eq!(None, graph.input_names(NodeIndex::new(5)));
+    }
+
+    #[test]
+    fn merge() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        let mut other = TextureGraph::<i32>::new();
+        let other1 = other.add_node(Node::new(String::from("M1"), Box::new(Const(5))));
+        let other2 = other.add_node(Node::new(String::from("M2"), Box::new(Double{})));
+        other.add_edge(other1, other2, 0).unwrap();
+        other.generate_graph().unwrap();
+        let mapping = graph.merge(other);
+        assert_eq!(5, graph.node_count());
+        assert_eq!(3, graph.edge_count());
+        assert_eq!(vec![(0, mapping[&other1])], graph.input_connections(mapping[&other2]));
+        assert!(graph.get_generated_node(&mapping[&other2]).is_none());
+        assert!(graph.graph_complete());
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&10), graph.get_generated_node(&mapping[&other2]));
+        assert_eq!(Some(&3), graph.get_generated_node(&index3));
+    }
+
+    #[test]
+    fn set_resolution() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Area(2, 2))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Const(1))));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&8), graph.get_generated_node(&index2));
+        assert_eq!(Err(GraphError::InvalidResolution { width: 0, height: 4 }), graph.set_resolution(0, 4));
+        assert_eq!(Some(&8), graph.get_generated_node(&index2));
+        graph.set_resolution(3, 4).unwrap();
+        assert!(graph.get_generated_node(&index1).is_none());
+        assert!(graph.get_generated_node(&index2).is_none());
+        assert_eq!(Some(&1), graph.get_generated_node(&index3));
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&24), graph.get_generated_node(&index2));
+    }
+
+    #[test]
+    fn generate_graph_report() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Add{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Double{})));
+        let index5 = graph.add_node(Node::new(String::from("N5"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        graph.add_edge(index1, index5, 0).unwrap();
+        let errors = graph.generate_graph_report();
+        // N2 misses an input and N4 misses its only input. N3 depends on N2, so it is skipped instead of reported.
+        assert_eq!(2, errors.len());
+        assert!(errors.contains(&(index2, GraphError::MissingInput { node: index2, input: 1 })));
+        assert!(errors.contains(&(index4, GraphError::MissingInput { node: index4, input: 0 })));
+        assert!(graph.get_generated_node(&index3).is_none());
+        assert_eq!(Some(&2), graph.get_generated_node(&index5));
+    }
+
+    #[test]
+    fn complete_over_connected() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        assert!(graph.add_edge(index1, index2, 0).is_err());
+        graph.g.add_edge(index1, index2, 0);
+        assert!(!graph.node_complete(index2));
+    }
+
+    #[test]
+    fn complete_duplicate_target() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_eq!(1, graph.edge_count());
+        graph.g.add_edge(index1, index3, 0);
+        assert!(!graph.node_complete(index3));
+        graph.add_edge(index1, index3, 1).unwrap();
+        assert_eq!(3, graph.edge_count());
+        assert!(!graph.node_complete(index3));
+    }
+
+    #[test]
+    fn complete_cache_consistent() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Double{})));
+        let recomputed = |graph: &TextureGraph<i32>| graph.g.node_indices().all(|i| graph.node_complete(i));
+        assert_eq!(vec![index3, index4], graph.incomplete_nodes());
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        assert_eq!(recomputed(&graph), graph.graph_complete());
+        graph.add_edge(index3, index4, 0).unwrap();
+        assert!(graph.graph_complete());
+        assert_eq!(recomputed(&graph), graph.graph_complete());
+        graph.add_edge(index1, index3, 1).unwrap();
+        assert_eq!(recomputed(&graph), graph.graph_complete());
+        graph.disconnect_edge(index3, 0).unwrap();
+        assert!(!graph.graph_complete());
+        assert_eq!(recomputed(&graph), graph.graph_complete());
+        assert_eq!(vec![index3], graph.incomplete_nodes());
+        assert!(graph.disconnect_edge(index3, 0).is_err());
+        graph.duplicate_node(index3).unwrap();
+        assert_eq!(2, graph.incomplete_nodes().len());
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_eq!(recomputed(&graph), graph.graph_complete());
+        let subgraph = graph.extract_subgraph(&[index3, index4]);
+        assert_eq!(recomputed(&subgraph), subgraph.graph_complete());
+    }
+
+    #[test]
+    fn disconnect_edge_invalidates() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.generate_graph().unwrap();
+        graph.disconnect_edge(index2, 0).unwrap();
+        assert_eq!(Err(GraphError::NotConnected { node: index2, input: 0 }), graph.disconnect_edge(index2, 0));
+        assert_eq!(0, graph.g.edge_count());
+        assert!(graph.get_generated_node(&index1).is_some());
+        assert!(graph.get_generated_node(&index2).is_none());
+    }
+
     #[test]
     fn generate_node() {
         let mut graph = TextureGraph::<i32>::new();
@@ -533,4 +1847,298 @@ mod tests {
 
     }
 
+    #[test]
+    fn to_dot() {
+        let mut graph = TextureGraph::<i32>::new();
+        let node1 = Node::new(String::from("N1"), Box::new(Const(1)));
+        let node2 = Node::new(String::from("N2"), Box::new(Const(2)));
+        let node3 = Node::new(String::from("N3"), Box::new(Add{}));
+        let index1 = graph.add_node(node1);
+        let index2 = graph.add_node(node2);
+        let index3 = graph.add_node(node3);
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("0 [label=\"N1\"];"));
+        assert!(dot.contains("1 [label=\"N2\"];"));
+        assert!(dot.contains("2 [label=\"N3\"];"));
+        assert!(dot.contains("0 -> 2 [label=\"0\"];"));
+        assert!(dot.contains("1 -> 2 [label=\"1\"];"));
+    }
+
+    #[test]
+    fn ancestors_descendants_chain() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        let ancestors: HashSet<_> = graph.ancestors(index3).into_iter().collect();
+        assert_eq!(HashSet::from([index1, index2]), ancestors);
+        let descendants: HashSet<_> = graph.descendants(index1).into_iter().collect();
+        assert_eq!(HashSet::from([index2, index3]), descendants);
+        assert!(graph.ancestors(index1).is_empty());
+        assert!(graph.descendants(index3).is_empty());
+    }
+
+    #[test]
+    fn ancestors_descendants_diamond() {
+        let mut graph = TextureGraph::<i32>::new();
+        let top = graph.add_node(Node::new(String::from("Top"), Box::new(Const(1))));
+        let left = graph.add_node(Node::new(String::from("Left"), Box::new(Double{})));
+        let right = graph.add_node(Node::new(String::from("Right"), Box::new(Double{})));
+        let bottom = graph.add_node(Node::new(String::from("Bottom"), Box::new(Add{})));
+        let other = graph.add_node(Node::new(String::from("Other"), Box::new(Const(2))));
+        graph.add_edge(top, left, 0).unwrap();
+        graph.add_edge(top, right, 0).unwrap();
+        graph.add_edge(left, bottom, 0).unwrap();
+        graph.add_edge(right, bottom, 1).unwrap();
+        let ancestors: HashSet<_> = graph.ancestors(bottom).into_iter().collect();
+        assert_eq!(HashSet::from([top, left, right]), ancestors);
+        let descendants: HashSet<_> = graph.descendants(top).into_iter().collect();
+        assert_eq!(HashSet::from([left, right, bottom]), descendants);
+        assert_eq!(vec![top], graph.ancestors(left));
+        assert_eq!(vec![bottom], graph.descendants(right));
+        assert!(graph.ancestors(other).is_empty());
+        assert!(graph.descendants(other).is_empty());
+    }
+
+    #[test]
+    fn structural_hash() {
+        let mut graph = TextureGraph::<i32>::new();
+        let top = graph.add_node(Node::new(String::from("Top"), Box::new(Const(1))));
+        let left = graph.add_node(Node::new(String::from("Left"), Box::new(Double{})));
+        let right = graph.add_node(Node::new(String::from("Right"), Box::new(Double{})));
+        let bottom = graph.add_node(Node::new(String::from("Bottom"), Box::new(Add{})));
+        graph.add_edge(top, left, 0).unwrap();
+        graph.add_edge(top, right, 0).unwrap();
+        graph.add_edge(left, bottom, 0).unwrap();
+        graph.add_edge(right, bottom, 1).unwrap();
+        let hashes = graph.structural_hashes();
+        for index in [top, left, right, bottom] {
+            assert_eq!(hashes[&index], graph.structural_hash(index));
+        }
+        // Editing a parameter changes the hashes of the node and the nodes it feeds into.
+        graph.set_function(top, Box::new(Const(2))).unwrap();
+        assert_ne!(hashes[&top], graph.structural_hash(top));
+        assert_ne!(hashes[&bottom], graph.structural_hash(bottom));
+        graph.set_function(top, Box::new(Const(1))).unwrap();
+        assert_eq!(hashes[&bottom], graph.structural_hash(bottom));
+    }
+
+    #[test]
+    fn unused_nodes() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        let unused = graph.add_node(Node::new(String::from("Unused"), Box::new(Const(2))));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_eq!(vec![unused], graph.unused_nodes());
+    }
+
+    #[test]
+    fn output_nodes_chain() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_eq!(vec![index3], graph.output_nodes());
+    }
+
+    #[test]
+    fn output_nodes_parallel() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index1, index3, 0).unwrap();
+        let outputs: HashSet<_> = graph.output_nodes().into_iter().collect();
+        assert_eq!(HashSet::from([index2, index3]), outputs);
+    }
+
+    #[test]
+    fn eval_order() {
+        let build = || {
+            let mut graph = TextureGraph::<i32>::new();
+            let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+            let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+            let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+            let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Double{})));
+            graph.add_edge(index3, index4, 0).unwrap();
+            graph.add_edge(index1, index3, 0).unwrap();
+            graph.add_edge(index2, index3, 1).unwrap();
+            graph.add_edge(index1, index2, 0).unwrap();
+            graph
+        };
+        let mut expected = build();
+        expected.generate_graph().unwrap();
+        let mut graph = build();
+        let order = graph.eval_order();
+        assert_eq!(4, order.len());
+        assert_eq!(Err(GraphError::InputNotGenerated { node: order[3], input: 0 }), graph.eval_one(order[3]));
+        for &index in &order {
+            graph.eval_one(index).unwrap();
+        }
+        for index in order {
+            assert_eq!(expected.get_generated_node(&index).copied(), graph.get_generated_node(&index).copied());
+        }
+        assert_eq!(Some(&6), graph.get_generated_node(&NodeIndex::new(3)));
+        assert_eq!(Err(GraphError::UnknownNode(NodeIndex::new(9))), graph.eval_one(NodeIndex::new(9)));
+        let index5 = graph.add_node(Node::new(String::from("N5"), Box::new(Double{})));
+        assert_eq!(Err(GraphError::MissingInput { node: index5, input: 0 }), graph.eval_one(index5));
+    }
+
+    #[derive(Clone)]
+    struct Cancel(Arc<AtomicBool>);
+    impl TextureTransformer<i32> for Cancel {
+        fn generate(&self, inputs: Vec<&i32>) -> i32 {
+            self.0.store(true, Ordering::Relaxed);
+            *inputs[0]
+        }
+
+        fn inputs(&self) -> usize {
+            1
+        }
+
+        fn params_hash(&self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn generate_graph_cancellable() {
+        let mut graph = TextureGraph::<i32>::new();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Cancel(cancel.clone()))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.add_edge(index2, index3, 0).unwrap();
+        let set = AtomicBool::new(true);
+        assert_eq!(Err(GraphError::Cancelled), graph.generate_graph_cancellable(&set));
+        assert_eq!(0, graph.stats().cached_results);
+        // The second node cancels the generation, which stops before the third node.
+        assert_eq!(Err(GraphError::Cancelled), graph.generate_graph_cancellable(&cancel));
+        assert_eq!(Some(&1), graph.get_generated_node(&index2));
+        assert_eq!(None, graph.get_generated_node(&index3));
+        assert_eq!(Ok(()), graph.generate_graph_cancellable(&AtomicBool::new(false)));
+        assert_eq!(Some(&2), graph.get_generated_node(&index3));
+    }
+
+    #[test]
+    fn generate_node_ref() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        assert_eq!(Err(GraphError::InputNotGenerated { node: index3, input: 0 }), graph.generate_node_ref(index3));
+        assert_eq!(Ok(&1), graph.generate_node_ref(index1));
+        assert_eq!(Ok(&2), graph.generate_node_ref(index2));
+        let result = *graph.generate_node_ref(index3).unwrap();
+        assert_eq!(3, result);
+        assert_eq!(Some(&result), graph.get_generated_node(&index3));
+    }
+
+    #[test]
+    fn add_identical_edge_keeps_cache() {
+        let mut graph = TextureGraph::<i32>::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Counted(3, count.clone()))));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        graph.add_edge(index3, index4, 0).unwrap();
+        graph.generate_graph().unwrap();
+        assert_eq!(1, count.load(Ordering::SeqCst));
+        let hashes = graph.structural_hashes();
+        graph.add_edge(index1, index3, 0).unwrap();
+        assert_eq!(hashes, graph.structural_hashes());
+        graph.regenerate_changed().unwrap();
+        assert_eq!(1, count.load(Ordering::SeqCst));
+        assert_eq!(Some(&3), graph.get_generated_node(&index3));
+        assert_eq!(Some(&6), graph.get_generated_node(&index4));
+        graph.add_edge(index2, index3, 0).unwrap();
+        assert_ne!(hashes[&index3], graph.structural_hash(index3));
+        assert!(graph.get_generated_node(&index3).is_none());
+        assert!(graph.get_generated_node(&index4).is_none());
+    }
+
+    #[test]
+    fn clear_results() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Double{})));
+        graph.add_edge(index1, index2, 0).unwrap();
+        graph.generate_graph().unwrap();
+        graph.clear_results();
+        assert_eq!(2, graph.node_count());
+        assert!(graph.get_generated_node(&index1).is_none());
+        assert!(graph.get_generated_node(&index2).is_none());
+        graph.generate_graph().unwrap();
+        assert_eq!(Some(&1), graph.get_generated_node(&index1));
+        assert_eq!(Some(&2), graph.get_generated_node(&index2));
+    }
+
+    #[test]
+    fn duplicate_node() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        let copy = graph.duplicate_node(index3).unwrap();
+        assert_eq!("N3 copy", graph.get_node(copy).unwrap().name);
+        assert_eq!(graph.ancestors(index3).len(), graph.ancestors(copy).len());
+        assert_eq!(graph.g.find_edge(index1, index3).map(|e| graph.g[e]), graph.g.find_edge(index1, copy).map(|e| graph.g[e]));
+        assert_eq!(graph.g.find_edge(index2, index3).map(|e| graph.g[e]), graph.g.find_edge(index2, copy).map(|e| graph.g[e]));
+        graph.generate_graph().unwrap();
+        assert_eq!(graph.get_generated_node(&index3).copied(), graph.get_generated_node(&copy).copied());
+    }
+
+    #[test]
+    fn duplicate_unknown_node() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        graph.g.remove_node(index);
+        assert!(graph.duplicate_node(index).is_err());
+    }
+
+    #[test]
+    fn extract_subgraph() {
+        let mut graph = TextureGraph::<i32>::new();
+        let index1 = graph.add_node(Node::new(String::from("N1"), Box::new(Const(1))));
+        let index2 = graph.add_node(Node::new(String::from("N2"), Box::new(Const(2))));
+        let index3 = graph.add_node(Node::new(String::from("N3"), Box::new(Add{})));
+        let index4 = graph.add_node(Node::new(String::from("N4"), Box::new(Double{})));
+        graph.add_edge(index1, index3, 0).unwrap();
+        graph.add_edge(index2, index3, 1).unwrap();
+        graph.add_edge(index3, index4, 0).unwrap();
+        assert_eq!(2, graph.extract_subgraph(&[index2, index3, index2, NodeIndex::new(7)]).node_count());
+        let (mut subgraph, mapping) = graph.extract_subgraph_with_map(&[index2, index3]);
+        assert_eq!(2, mapping.len());
+        let indices = [mapping[&index2], mapping[&index3]];
+        assert_eq!(2, subgraph.node_count());
+        assert_eq!(1, subgraph.g.edge_count());
+        assert_eq!("N2", subgraph.get_node(indices[0]).unwrap().name);
+        assert_eq!("N3", subgraph.get_node(indices[1]).unwrap().name);
+        let edge = subgraph.g.find_edge(indices[0], indices[1]).unwrap();
+        assert_eq!(1, subgraph.g[edge]);
+        assert!(!subgraph.node_complete(indices[1]));
+        assert!(subgraph.add_edge(indices[0], indices[1], 0).is_ok());
+        subgraph.generate_graph().unwrap();
+        assert_eq!(Some(&4), subgraph.get_generated_node(&indices[1]));
+    }
+
 }
\ No newline at end of file
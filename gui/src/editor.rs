@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use graph::{Node, TextureGraph};
+use image::Rgb32FImage;
+use serde::{Deserialize, Serialize};
+use texture_generators::{save_texture, ExportFormat, GeneratorProperties};
+
+use crate::preview::hash_image;
+use crate::templates::NodeParams;
+
+/// Identifies a node in the editor.
+pub type NodeId = usize;
+
+/// A connection from the output of one node to an input of another node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Connection {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub input: usize,
+}
+
+/// The nodes and connections the user has built in the editor.
+#[derive(Default, Serialize, Deserialize)]
+pub struct EditorState {
+    pub nodes: Vec<NodeParams>,
+    pub connections: Vec<Connection>,
+}
+
+impl EditorState {
+    /// Adds a node with the given parameters, and returns its id.
+    pub fn add_node(&mut self, params: NodeParams) -> NodeId {
+        self.nodes.push(params);
+        self.nodes.len() - 1
+    }
+
+    /// Connects the output of a node to an input of another node, replacing any connection to that input.
+    pub fn connect(&mut self, from: NodeId, to: NodeId, input: usize) {
+        self.connections.retain(|c| !(c.to == to && c.input == input));
+        self.connections.push(Connection { from, to, input });
+    }
+
+    /// Returns the given node and all nodes that feed into it.
+    fn required_nodes(&self, node: NodeId) -> Vec<NodeId> {
+        let mut required = vec![node];
+        let mut seen = HashSet::from([node]);
+        let mut i = 0;
+        while i < required.len() {
+            let current = required[i];
+            for connection in self.connections.iter().filter(|c| c.to == current) {
+                if seen.insert(connection.from) {
+                    required.push(connection.from);
+                }
+            }
+            i += 1;
+        }
+        required
+    }
+}
+
+/// State of the editor that is not part of the graph itself, such as generated results.
+/// Generated results are not saved, and are regenerated after loading.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphState {
+    #[serde(skip)]
+    pub cached_results: HashMap<NodeId, Rgb32FImage>,
+    /// The hash of every cached result, computed once when the result is stored so previews do not hash it every frame.
+    #[serde(skip)]
+    result_hashes: HashMap<NodeId, u64>,
+    width: u32,
+    height: u32,
+}
+
+impl Default for GraphState {
+    fn default() -> Self {
+        let properties = GeneratorProperties::default();
+        GraphState { cached_results: HashMap::new(), result_hashes: HashMap::new(), width: properties.width, height: properties.height }
+    }
+}
+
+/// The largest width or height that can be set as the resolution of the generated textures.
+pub const MAX_RESOLUTION: u32 = 8192;
+
+/// Checks that a resolution is not empty, and not so large that generating it would exhaust memory.
+fn validate_resolution(width: u32, height: u32) -> Result<(), String> {
+    if width == 0 || height == 0 || width > MAX_RESOLUTION || height > MAX_RESOLUTION {
+        return Err(format!("Invalid resolution {}x{}, width and height must be between 1 and {}", width, height, MAX_RESOLUTION));
+    }
+    Ok(())
+}
+
+/// Actions requested by the user from within a node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GraphResponse {
+    /// Save the result of the node to an image file.
+    ExportNode(NodeId),
+}
+
+impl GraphState {
+    /// Returns the width and height that every generator produces.
+    pub fn resolution(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Sets the width and height that every generator produces, as set in the side panel.
+    /// Clears the generated results, as they no longer match the resolution.
+    /// Fails without changing the resolution if either dimension is zero or larger than `MAX_RESOLUTION`.
+    pub fn set_resolution(&mut self, width: u32, height: u32) -> Result<(), String> {
+        validate_resolution(width, height)?;
+        if (width, height) != self.resolution() {
+            self.width = width;
+            self.height = height;
+            self.cached_results.clear();
+            self.result_hashes.clear();
+        }
+        Ok(())
+    }
+
+    /// Returns the hash of the cached result of a node, as computed by `hash_image` when the result was stored.
+    pub fn result_hash(&self, node: NodeId) -> Option<u64> {
+        self.result_hashes.get(&node).copied()
+    }
+
+    /// Removes the cached result of a node, along with its hash.
+    fn remove_result(&mut self, node: NodeId) {
+        self.cached_results.remove(&node);
+        self.result_hashes.remove(&node);
+    }
+
+    /// Checks whether a node has a result that can be exported. The export button is disabled otherwise.
+    pub fn can_export(&self, node: NodeId) -> bool {
+        self.cached_results.contains_key(&node)
+    }
+
+    /// Writes the result of a node to a PNG file at the path chosen by the user.
+    /// Fails if the node has not been evaluated yet, or the file could not be written.
+    pub fn export_node(&self, node: NodeId, path: &Path) -> Result<(), String> {
+        let result = match self.cached_results.get(&node) {
+            Some(result) => result,
+            None => return Err(format!("Node {} has no result to export", node)),
+        };
+        save_texture(result, path, ExportFormat::Png { srgb: false }, None).map_err(|e| e.to_string())
+    }
+}
+
+/// Evaluates a node of the editor, by building a texture graph of the node and everything that feeds into it.
+/// Every generator is built with the resolution of the state, so the inputs of transformers always match in size.
+/// The results of all evaluated nodes are stored in the cached results of the state.
+/// Nodes that do not contribute to the evaluated node are ignored, so unfinished parts of the editor do not cause errors.
+/// Fails if the node, or any node it depends on, is missing connections or has invalid inputs.
+pub fn evaluate(editor: &EditorState, state: &mut GraphState, node: NodeId) -> Result<(), String> {
+    if node >= editor.nodes.len() {
+        return Err(format!("Unknown node {}", node));
+    }
+    validate_resolution(state.width, state.height)?;
+    let properties = GeneratorProperties { width: state.width, height: state.height };
+    let required = editor.required_nodes(node);
+    let mut graph = TextureGraph::<Rgb32FImage>::new();
+    let mut indices = HashMap::new();
+    for &id in &required {
+        let params = &editor.nodes[id];
+        let index = graph.add_node(Node::new(String::from(params.template().label()), params.build_node(properties.clone())));
+        indices.insert(id, index);
+    }
+    for connection in editor.connections.iter().filter(|c| indices.contains_key(&c.to)) {
+        graph.add_edge(indices[&connection.from], indices[&connection.to], connection.input).map_err(|e| e.to_string())?;
+    }
+    if let Some(&id) = required.iter().find(|id| !graph.node_complete(indices[id])) {
+        state.remove_result(node);
+        return Err(format!("Node {} is missing connections", id));
+    }
+    if let Err(e) = graph.generate_graph() {
+        state.remove_result(node);
+        return Err(e.to_string());
+    }
+    for (id, index) in indices {
+        if let Some(result) = graph.get_generated_node(&index) {
+            state.result_hashes.insert(id, hash_image(result));
+            state.cached_results.insert(id, result.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use crate::editor::{evaluate, EditorState, GraphState, MAX_RESOLUTION};
+    use crate::preview::hash_image;
+    use crate::templates::{NodeParams, NodeTemplate};
+    use crate::values::select_blend_mode;
+
+    #[test]
+    fn evaluate_blend() {
+        let mut editor = EditorState::default();
+        let mut state = GraphState::default();
+        let red = editor.add_node(NodeParams::SolidColor { color: Rgb([1.0, 0.0, 0.0]) });
+        let white = editor.add_node(NodeTemplate::SolidColor.default_params());
+        let blend = editor.add_node(NodeTemplate::Blend.default_params());
+        let unfinished = editor.add_node(NodeTemplate::Blend.default_params());
+        editor.connect(red, blend, 0);
+        editor.connect(white, blend, 1);
+        editor.connect(red, unfinished, 0);
+        assert!(evaluate(&editor, &mut state, blend).is_ok());
+        assert!(state.cached_results[&blend].pixels().all(|pix| *pix == Rgb([1.0, 0.0, 0.0])));
+        assert!(state.cached_results.contains_key(&red));
+        assert!(state.cached_results.contains_key(&white));
+        assert!(!state.cached_results.contains_key(&unfinished));
+    }
+
+    #[test]
+    fn evaluate_missing_connection() {
+        let mut editor = EditorState::default();
+        let mut state = GraphState::default();
+        let red = editor.add_node(NodeParams::SolidColor { color: Rgb([1.0, 0.0, 0.0]) });
+        let blend = editor.add_node(NodeTemplate::Blend.default_params());
+        editor.connect(red, blend, 0);
+        assert!(evaluate(&editor, &mut state, blend).is_err());
+        assert!(!state.cached_results.contains_key(&blend));
+        assert!(evaluate(&editor, &mut state, red).is_ok());
+        assert!(state.cached_results.contains_key(&red));
+        assert!(evaluate(&editor, &mut state, 5).is_err());
+    }
+
+    #[test]
+    fn export_node() {
+        let mut editor = EditorState::default();
+        let mut state = GraphState::default();
+        let red = editor.add_node(NodeParams::SolidColor { color: Rgb([1.0, 0.0, 0.0]) });
+        let path = std::env::temp_dir().join("gui_export_node.png");
+        assert!(!state.can_export(red));
+        assert!(state.export_node(red, &path).is_err());
+        evaluate(&editor, &mut state, red).unwrap();
+        assert!(state.can_export(red));
+        assert!(state.export_node(red, &path).is_ok());
+        let loaded = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.pixels().all(|pix| *pix == Rgb([255, 0, 0])));
+    }
+
+    #[test]
+    fn evaluate_selected_blend_mode() {
+        let mut editor = EditorState::default();
+        let mut state = GraphState::default();
+        let gray = editor.add_node(NodeParams::SolidColor { color: Rgb([0.5, 0.5, 0.5]) });
+        let blend = editor.add_node(NodeTemplate::Blend.default_params());
+        editor.connect(gray, blend, 0);
+        editor.connect(gray, blend, 1);
+        if let NodeParams::Blend { option } = &mut editor.nodes[blend] {
+            select_blend_mode(option, 0);
+        }
+        evaluate(&editor, &mut state, blend).unwrap();
+        assert!(state.cached_results[&blend].pixels().all(|pix| *pix == Rgb([1.0, 1.0, 1.0])));
+    }
+
+    #[test]
+    fn global_resolution() {
+        let mut editor = EditorState::default();
+        let mut state = GraphState::default();
+        let lines = editor.add_node(NodeTemplate::Lines.default_params());
+        let checkerboard = editor.add_node(NodeTemplate::Checkerboard.default_params());
+        let blend = editor.add_node(NodeTemplate::Blend.default_params());
+        editor.connect(lines, blend, 0);
+        editor.connect(checkerboard, blend, 1);
+        assert_eq!((128, 128), state.resolution());
+        evaluate(&editor, &mut state, blend).unwrap();
+        assert_eq!(Some(hash_image(&state.cached_results[&blend])), state.result_hash(blend));
+        assert!(state.set_resolution(64, 32).is_ok());
+        assert!(state.cached_results.is_empty());
+        assert_eq!(None, state.result_hash(blend));
+        evaluate(&editor, &mut state, blend).unwrap();
+        for id in [lines, checkerboard, blend] {
+            assert_eq!((64, 32), state.cached_results[&id].dimensions());
+        }
+    }
+
+    #[test]
+    fn invalid_resolution() {
+        let mut state = GraphState::default();
+        assert!(state.set_resolution(0, 32).is_err());
+        assert!(state.set_resolution(32, 0).is_err());
+        assert!(state.set_resolution(MAX_RESOLUTION + 1, 32).is_err());
+        assert!(state.set_resolution(MAX_RESOLUTION, MAX_RESOLUTION).is_ok());
+        assert_eq!((MAX_RESOLUTION, MAX_RESOLUTION), state.resolution());
+    }
+}
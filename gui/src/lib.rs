@@ -0,0 +1,5 @@
+pub mod preview;
+pub mod templates;
+pub mod editor;
+pub mod persistence;
+pub mod values;
@@ -1,24 +1,256 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+use std::collections::HashMap;
+
 use eframe::egui;
+use gui::editor::{evaluate, EditorState, GraphResponse, GraphState, NodeId, MAX_RESOLUTION};
+use gui::persistence::{load_graph, save_graph};
+use gui::preview::{thumbnail_rgba, PreviewCache, PreviewKey};
+use gui::templates::{NodeParams, NodeTemplate};
+use gui::values::{blend_mode_widget, clipped_fraction, color_widget};
+use texture_generators::GeneratorProperties;
 
-fn main() -> Result<(), eframe::Error> {
-    // env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+/// The largest width or height of the thumbnail drawn in every node.
+const PREVIEW_RESOLUTION: u32 = 96;
 
-    let options = eframe::NativeOptions {
-        initial_window_size: Some(egui::vec2(800.0, 640.0)),
-        ..Default::default()
-    };
+/// Everything the application keeps between frames.
+#[derive(Default)]
+struct TextureApp {
+    editor: EditorState,
+    state: GraphState,
+    /// The node that was clicked last. Its result is regenerated whenever the graph changes.
+    active: Option<NodeId>,
+    /// Why the active node could not be evaluated, if it failed.
+    error: Option<String>,
+    /// The uploaded thumbnail of every node with a result, along with the fraction of its pixels that are clipped on export.
+    previews: HashMap<NodeId, PreviewCache<(egui::TextureHandle, f32)>>,
+}
+
+impl TextureApp {
+    /// Makes a node active and evaluates it, storing the results of the node and everything that feeds into it.
+    fn activate(&mut self, node: NodeId) {
+        self.active = Some(node);
+        self.error = evaluate(&self.editor, &mut self.state, node).err();
+    }
 
-    eframe::run_simple_native("Texture generator", options, move |ctx, _frame| {
+    /// Evaluates the active node again after the graph has changed.
+    fn refresh(&mut self) {
+        if let Some(node) = self.active {
+            self.activate(node);
+        }
+    }
+
+    /// Asks for a file and saves the graph to it.
+    fn save(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().add_filter("Texture graph", &["json"]).save_file() {
+            self.error = save_graph(&self.editor, &self.state, &path).err();
+        }
+    }
+
+    /// Asks for a file and replaces the graph with the one saved in it. Nothing is evaluated until a node is clicked.
+    fn load(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("Texture graph", &["json"]).pick_file() else {
+            return;
+        };
+        match load_graph(&path) {
+            Ok((editor, state)) => {
+                self.editor = editor;
+                self.state = state;
+                self.active = None;
+                self.error = None;
+                self.previews.clear();
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// The name of a node as shown in the editor.
+    fn node_label(&self, node: NodeId) -> String {
+        format!("{} #{}", self.editor.nodes[node].template().label(), node)
+    }
+
+    fn show(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
-            ui.heading("Toolbar");
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button("File", |ui| {
+                    if ui.button("Save graph…").clicked() {
+                        ui.close_menu();
+                        self.save();
+                    }
+                    if ui.button("Load graph…").clicked() {
+                        ui.close_menu();
+                        self.load();
+                    }
+                });
+                ui.menu_button("Add node", |ui| {
+                    for kind in NodeTemplate::all_kinds() {
+                        if ui.button(kind.label()).clicked() {
+                            self.editor.add_node(kind.default_params());
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
         });
         egui::SidePanel::right("configuration settings").show(ctx, |ui| {
-            ui.heading("Right section")
+            ui.heading("Active node");
+            match self.active {
+                Some(node) => ui.label(self.node_label(node)),
+                None => ui.label("Click a node to evaluate it"),
+            };
+            if let Some(error) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            ui.separator();
+            self.resolution_ui(ui);
         });
+        let mut responses = Vec::new();
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Main window");
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for node in 0..self.editor.nodes.len() {
+                    responses.extend(self.node_ui(ui, node));
+                }
+            });
+        });
+        for response in responses {
+            self.handle_response(response);
+        }
+    }
+
+    /// Draws the width and height that every generator produces. Changing them evaluates the active node again.
+    fn resolution_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Resolution");
+        let (mut width, mut height) = self.state.resolution();
+        let changed = ui.horizontal(|ui| {
+            let width_changed = ui.add(egui::DragValue::new(&mut width).clamp_range(1..=MAX_RESOLUTION).suffix(" px")).changed();
+            ui.label("×");
+            let height_changed = ui.add(egui::DragValue::new(&mut height).clamp_range(1..=MAX_RESOLUTION).suffix(" px")).changed();
+            width_changed || height_changed
+        }).inner;
+        if changed {
+            match self.state.set_resolution(width, height) {
+                Ok(()) => self.refresh(),
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+
+    /// Carries out an action requested from within a node, after the nodes have been drawn.
+    fn handle_response(&mut self, response: GraphResponse) {
+        match response {
+            GraphResponse::ExportNode(node) => {
+                let dialog = rfd::FileDialog::new()
+                    .add_filter("PNG image", &["png"])
+                    .set_file_name(&format!("{}.png", self.editor.nodes[node].template().label()));
+                if let Some(path) = dialog.save_file() {
+                    self.error = self.state.export_node(node, &path).err();
+                }
+            }
+        }
+    }
+
+    /// Draws a node with its inputs and a thumbnail of its result. Clicking the title of the node evaluates it.
+    /// Returns the action requested with the buttons of the node, if any.
+    fn node_ui(&mut self, ui: &mut egui::Ui, node: NodeId) -> Option<GraphResponse> {
+        let mut changed = false;
+        let mut response = None;
+        ui.group(|ui| {
+            if ui.selectable_label(self.active == Some(node), self.node_label(node)).clicked() {
+                self.activate(node);
+            }
+            let input_names = self.editor.nodes[node].build_node(GeneratorProperties::default()).input_names();
+            for (input, name) in input_names.iter().enumerate() {
+                changed |= self.input_ui(ui, node, input, name);
+            }
+            changed |= self.params_ui(ui, node);
+            self.preview_ui(ui, node);
+            let export = egui::Button::new("Export image…");
+            if ui.add_enabled(self.state.can_export(node), export).on_disabled_hover_text("Click the node to evaluate it first").clicked() {
+                response = Some(GraphResponse::ExportNode(node));
+            }
+        });
+        if changed {
+            self.refresh();
+        }
+        response
+    }
+
+    /// Draws the widgets that edit the parameters of a node. Returns whether any parameter changed.
+    fn params_ui(&mut self, ui: &mut egui::Ui, node: NodeId) -> bool {
+        match &mut self.editor.nodes[node] {
+            NodeParams::SolidColor { color } => color_widget(ui, color),
+            NodeParams::Gradient { start: color1, end: color2, .. }
+            | NodeParams::Checkerboard { color1, color2, .. }
+            | NodeParams::Lines { color1, color2, .. } => color_widget(ui, color1) | color_widget(ui, color2),
+            NodeParams::Blend { option } => blend_mode_widget(ui, ("blend mode", node), option),
+        }
+    }
+
+    /// Draws the thumbnail of the result of a node. The thumbnail is only uploaded again when the result changes.
+    /// Solid colors are drawn as a swatch, so they show their color before they are evaluated.
+    /// Results with pixels outside of 0..1 are marked with the fraction of pixels that an 8-bit export clips.
+    fn preview_ui(&mut self, ui: &mut egui::Ui, node: NodeId) {
+        if let NodeParams::SolidColor { color } = &self.editor.nodes[node] {
+            let [r, g, b] = color.0.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+            let size = egui::vec2(PREVIEW_RESOLUTION as f32, PREVIEW_RESOLUTION as f32 / 4.0);
+            let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, egui::Color32::from_rgb(r, g, b));
+            return;
+        }
+        let (Some(result), Some(result_hash)) = (self.state.cached_results.get(&node), self.state.result_hash(node)) else {
+            self.previews.remove(&node);
+            ui.weak("Not evaluated");
+            return;
+        };
+        let key = PreviewKey { result_hash, preview_resolution: PREVIEW_RESOLUTION };
+        let (texture, clipped) = self.previews.entry(node).or_default().get_or_rebuild(key, || {
+            let (width, height, pixels) = thumbnail_rgba(result, PREVIEW_RESOLUTION);
+            let image = egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels);
+            let texture = ui.ctx().load_texture(format!("preview {}", node), image, egui::TextureOptions::default());
+            (texture, clipped_fraction(result))
         });
-    })
-}
\ No newline at end of file
+        ui.image(texture.id(), texture.size_vec2());
+        ui.label(format!("{}x{}", result.width(), result.height()));
+        if *clipped > 0.0 {
+            ui.colored_label(ui.visuals().warn_fg_color, format!("{:.1}% clipped", clipped * 100.0))
+                .on_hover_text("Pixels outside 0..1 are clipped when exported to an 8-bit PNG");
+        }
+    }
+
+    /// Draws a combo box to choose the node connected to an input. Returns whether the connection changed.
+    fn input_ui(&mut self, ui: &mut egui::Ui, node: NodeId, input: usize, name: &str) -> bool {
+        let source = self.editor.connections.iter().find(|c| c.to == node && c.input == input).map(|c| c.from);
+        let mut selected = source;
+        ui.horizontal(|ui| {
+            ui.label(name);
+            egui::ComboBox::from_id_source((node, input))
+                .selected_text(selected.map_or(String::from("Not connected"), |from| self.node_label(from)))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut selected, None, "Not connected");
+                    for other in (0..self.editor.nodes.len()).filter(|&other| other != node) {
+                        ui.selectable_value(&mut selected, Some(other), self.node_label(other));
+                    }
+                });
+        });
+        if selected == source {
+            return false;
+        }
+        match selected {
+            Some(from) => self.editor.connect(from, node, input),
+            None => self.editor.connections.retain(|c| !(c.to == node && c.input == input)),
+        }
+        true
+    }
+}
+
+fn main() -> Result<(), eframe::Error> {
+    // env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+
+    let options = eframe::NativeOptions {
+        initial_window_size: Some(egui::vec2(800.0, 640.0)),
+        ..Default::default()
+    };
+
+    let mut app = TextureApp::default();
+    eframe::run_simple_native("Texture generator", options, move |ctx, _frame| app.show(ctx))
+}
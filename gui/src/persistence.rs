@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::editor::{EditorState, GraphState};
+
+/// Serializes colors as an array of their channels, as the image crate does not implement serde for pixels.
+pub(crate) mod color {
+    use core::Color;
+
+    use image::Rgb;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        color.0.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        Ok(Rgb(<[f32; 3]>::deserialize(deserializer)?))
+    }
+}
+
+#[derive(Serialize)]
+struct SavedGraphRef<'a> {
+    editor: &'a EditorState,
+    state: &'a GraphState,
+}
+
+#[derive(Deserialize)]
+struct SavedGraph {
+    editor: EditorState,
+    state: GraphState,
+}
+
+/// Converts the editor and its state to JSON. Generated results are not included.
+pub fn graph_to_string(editor: &EditorState, state: &GraphState) -> Result<String, String> {
+    serde_json::to_string_pretty(&SavedGraphRef { editor, state }).map_err(|e| e.to_string())
+}
+
+/// Reads an editor and its state from JSON, as written by `graph_to_string`.
+/// The returned state has no generated results, so every node is regenerated when it is evaluated.
+pub fn graph_from_str(json: &str) -> Result<(EditorState, GraphState), String> {
+    let saved: SavedGraph = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    Ok((saved.editor, saved.state))
+}
+
+/// Saves the editor and its state to the file chosen in the "Save graph…" dialog.
+pub fn save_graph(editor: &EditorState, state: &GraphState, path: &Path) -> Result<(), String> {
+    fs::write(path, graph_to_string(editor, state)?).map_err(|e| e.to_string())
+}
+
+/// Loads the editor and its state from the file chosen in the "Load graph…" dialog.
+pub fn load_graph(path: &Path) -> Result<(EditorState, GraphState), String> {
+    graph_from_str(&fs::read_to_string(path).map_err(|e| e.to_string())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+    use texture_generators::BlendOptions;
+
+    use crate::editor::{evaluate, EditorState, GraphState};
+    use crate::persistence::{graph_from_str, graph_to_string};
+    use crate::templates::{NodeParams, NodeTemplate};
+
+    #[test]
+    fn round_trip() {
+        let mut editor = EditorState::default();
+        let mut state = GraphState::default();
+        let red = editor.add_node(NodeParams::SolidColor { color: Rgb([1.0, 0.0, 0.0]) });
+        let lines = editor.add_node(NodeTemplate::Lines.default_params());
+        let blend = editor.add_node(NodeParams::Blend { option: BlendOptions::Add });
+        editor.connect(red, blend, 0);
+        editor.connect(lines, blend, 1);
+        evaluate(&editor, &mut state, blend).unwrap();
+
+        let json = graph_to_string(&editor, &state).unwrap();
+        let (loaded, loaded_state) = graph_from_str(&json).unwrap();
+        assert_eq!(editor.connections, loaded.connections);
+        assert_eq!(3, loaded.nodes.len());
+        assert!(matches!(loaded.nodes[red], NodeParams::SolidColor { color: Rgb([1.0, 0.0, 0.0]) }));
+        assert!(matches!(loaded.nodes[lines], NodeParams::Lines { scale: 4, .. }));
+        assert!(matches!(loaded.nodes[blend], NodeParams::Blend { option: BlendOptions::Add }));
+        assert!(loaded_state.cached_results.is_empty());
+        assert_eq!(json, graph_to_string(&loaded, &loaded_state).unwrap());
+    }
+}
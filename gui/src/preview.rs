@@ -0,0 +1,128 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use image::{Rgb32FImage, imageops::{self, FilterType}};
+
+/// Identifies the contents of a node preview. Pan and zoom of the editor are deliberately not part of the key,
+/// so a preview is only rebuilt when the underlying result or the preview resolution changes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PreviewKey {
+    pub result_hash: u64,
+    pub preview_resolution: u32,
+}
+
+impl PreviewKey {
+    /// Creates the key of a preview of the given result.
+    pub fn new(result: &Rgb32FImage, preview_resolution: u32) -> Self {
+        PreviewKey { result_hash: hash_image(result), preview_resolution }
+    }
+}
+
+/// Caches a preview handle, such as an uploaded texture, for a single node.
+pub struct PreviewCache<H> {
+    entry: Option<(PreviewKey, H)>,
+}
+
+impl<H> Default for PreviewCache<H> {
+    fn default() -> Self {
+        PreviewCache { entry: None }
+    }
+}
+
+impl<H> PreviewCache<H> {
+    /// Checks whether the cached handle was built for the given key.
+    pub fn is_hit(&self, key: &PreviewKey) -> bool {
+        matches!(&self.entry, Some((cached, _)) if cached == key)
+    }
+
+    /// Returns the cached handle, only calling `build` when the key differs from the cached key.
+    pub fn get_or_rebuild(&mut self, key: PreviewKey, build: impl FnOnce() -> H) -> &H {
+        if !self.is_hit(&key) {
+            self.entry = Some((key, build()));
+        }
+        &self.entry.as_ref().unwrap().1
+    }
+}
+
+/// Hashes the dimensions and pixel values of an image.
+pub fn hash_image(image: &Rgb32FImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    for value in image.as_raw() {
+        value.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Downsamples a result to fit in a square thumbnail, and converts it to 8-bit RGBA pixels as used by egui textures.
+/// Returns the width and height of the thumbnail along with the pixels.
+pub fn thumbnail_rgba(image: &Rgb32FImage, preview_resolution: u32) -> (u32, u32, Vec<u8>) {
+    let scale = preview_resolution as f32 / image.width().max(image.height()) as f32;
+    let width = ((image.width() as f32 * scale).round() as u32).max(1);
+    let height = ((image.height() as f32 * scale).round() as u32).max(1);
+    let resized = imageops::resize(image, width, height, FilterType::Triangle);
+    let pixels = resized.pixels()
+        .flat_map(|pix| {
+            let [r, g, b] = pix.0.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+            [r, g, b, 255]
+        })
+        .collect();
+    (width, height, pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, Rgb32FImage};
+
+    use crate::editor::{evaluate, EditorState, GraphState};
+    use crate::preview::{PreviewCache, PreviewKey, thumbnail_rgba};
+    use crate::templates::NodeTemplate;
+
+    #[test]
+    fn pan_is_cache_hit() {
+        let mut editor = EditorState::default();
+        let mut state = GraphState::default();
+        let lines = editor.add_node(NodeTemplate::Lines.default_params());
+        evaluate(&editor, &mut state, lines).unwrap();
+        let mut cache = PreviewCache::default();
+        let mut builds = 0;
+        cache.get_or_rebuild(PreviewKey::new(&state.cached_results[&lines], 64), || { builds += 1; "preview" });
+        // Adding a node that is not connected leaves the graph of the node untouched, like panning the editor.
+        editor.add_node(NodeTemplate::SolidColor.default_params());
+        evaluate(&editor, &mut state, lines).unwrap();
+        let panned = PreviewKey::new(&state.cached_results[&lines], 64);
+        assert!(cache.is_hit(&panned));
+        cache.get_or_rebuild(panned, || { builds += 1; "preview" });
+        assert_eq!(1, builds);
+        assert!(!cache.is_hit(&PreviewKey::new(&state.cached_results[&lines], 128)));
+    }
+
+    #[test]
+    fn changed_key_rebuilds() {
+        let mut cache = PreviewCache::default();
+        let mut builds = 0;
+        cache.get_or_rebuild(PreviewKey { result_hash: 42, preview_resolution: 64 }, || { builds += 1; 1 });
+        cache.get_or_rebuild(PreviewKey { result_hash: 42, preview_resolution: 128 }, || { builds += 1; 2 });
+        let handle = *cache.get_or_rebuild(PreviewKey { result_hash: 7, preview_resolution: 128 }, || { builds += 1; 3 });
+        assert_eq!(3, builds);
+        assert_eq!(3, handle);
+    }
+
+    #[test]
+    fn thumbnail() {
+        let image = Rgb32FImage::from_pixel(128, 64, Rgb([1.0, 0.5, 2.0]));
+        let (width, height, pixels) = thumbnail_rgba(&image, 32);
+        assert_eq!((32, 16), (width, height));
+        assert_eq!(32 * 16 * 4, pixels.len());
+        assert_eq!(&[255, 128, 255, 255], &pixels[0..4]);
+    }
+
+    #[test]
+    fn changed_result_rebuilds() {
+        let image = Rgb32FImage::from_pixel(8, 8, Rgb([1.0, 0.0, 0.0]));
+        let mut changed = image.clone();
+        changed.put_pixel(3, 3, Rgb([0.0, 1.0, 0.0]));
+        assert_eq!(PreviewKey::new(&image, 64), PreviewKey::new(&image.clone(), 64));
+        assert_ne!(PreviewKey::new(&image, 64), PreviewKey::new(&changed, 64));
+    }
+}
@@ -0,0 +1,162 @@
+use core::{Color, Gradient};
+
+use graph::TextureTransformer;
+use image::{Rgb, Rgb32FImage};
+use serde::{Deserialize, Serialize};
+use texture_generators::{BlendNode, BlendOptions, CheckerboardNode, GeneratorProperties, GradientNode, GradientNodeDirection,
+    LineOrientation, LinesNode, LinesPosition, SolidColorNode};
+
+/// The kinds of nodes that can be added in the editor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeTemplate {
+    SolidColor,
+    Gradient,
+    Checkerboard,
+    Lines,
+    Blend,
+}
+
+impl NodeTemplate {
+    /// Returns every template, in the order they are shown in the node finder.
+    pub fn all_kinds() -> Vec<NodeTemplate> {
+        vec![
+            NodeTemplate::SolidColor,
+            NodeTemplate::Gradient,
+            NodeTemplate::Checkerboard,
+            NodeTemplate::Lines,
+            NodeTemplate::Blend,
+        ]
+    }
+
+    /// The name of the template as shown in the editor.
+    pub fn label(&self) -> &'static str {
+        match self {
+            NodeTemplate::SolidColor => "Solid color",
+            NodeTemplate::Gradient => "Gradient",
+            NodeTemplate::Checkerboard => "Checkerboard",
+            NodeTemplate::Lines => "Lines",
+            NodeTemplate::Blend => "Blend",
+        }
+    }
+
+    /// The category the template is listed under in the node finder.
+    pub fn category(&self) -> &'static str {
+        match self {
+            NodeTemplate::Blend => "Transformer nodes",
+            _ => "Generator nodes",
+        }
+    }
+
+    /// Returns the parameters a new node of this template starts with.
+    pub fn default_params(&self) -> NodeParams {
+        let black = Rgb([0.0, 0.0, 0.0]);
+        let white = Rgb([1.0, 1.0, 1.0]);
+        match self {
+            NodeTemplate::SolidColor => NodeParams::SolidColor { color: white },
+            NodeTemplate::Gradient => NodeParams::Gradient {
+                start: black,
+                end: white,
+                direction: GradientNodeDirection::HORIZONTAL,
+            },
+            NodeTemplate::Checkerboard => NodeParams::Checkerboard { size_x: 1, size_y: 1, color1: black, color2: white },
+            NodeTemplate::Lines => NodeParams::Lines {
+                scale: 4,
+                thickness: 0.5,
+                position: LinesPosition::Start,
+                orientation: LineOrientation::Horizontal,
+                color1: black,
+                color2: white,
+            },
+            NodeTemplate::Blend => NodeParams::Blend { option: BlendOptions::Multiply },
+        }
+    }
+}
+
+/// The editable parameters of a node in the editor.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum NodeParams {
+    SolidColor {
+        #[serde(with = "crate::persistence::color")]
+        color: Color,
+    },
+    Gradient {
+        #[serde(with = "crate::persistence::color")]
+        start: Color,
+        #[serde(with = "crate::persistence::color")]
+        end: Color,
+        direction: GradientNodeDirection,
+    },
+    Checkerboard {
+        size_x: usize,
+        size_y: usize,
+        #[serde(with = "crate::persistence::color")]
+        color1: Color,
+        #[serde(with = "crate::persistence::color")]
+        color2: Color,
+    },
+    Lines {
+        scale: usize,
+        thickness: f32,
+        position: LinesPosition,
+        orientation: LineOrientation,
+        #[serde(with = "crate::persistence::color")]
+        color1: Color,
+        #[serde(with = "crate::persistence::color")]
+        color2: Color,
+    },
+    Blend { option: BlendOptions },
+}
+
+impl NodeParams {
+    /// Returns the template these parameters belong to.
+    pub fn template(&self) -> NodeTemplate {
+        match self {
+            NodeParams::SolidColor { .. } => NodeTemplate::SolidColor,
+            NodeParams::Gradient { .. } => NodeTemplate::Gradient,
+            NodeParams::Checkerboard { .. } => NodeTemplate::Checkerboard,
+            NodeParams::Lines { .. } => NodeTemplate::Lines,
+            NodeParams::Blend { .. } => NodeTemplate::Blend,
+        }
+    }
+
+    /// Builds the transformer that generates the output of the node. Generators produce images of the given properties.
+    pub fn build_node(&self, properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        match self.clone() {
+            NodeParams::SolidColor { color } => Box::new(SolidColorNode { color, properties }),
+            NodeParams::Gradient { start, end, direction } => Box::new(GradientNode::new(Gradient { start, end }, direction, properties)),
+            NodeParams::Checkerboard { size_x, size_y, color1, color2 } => Box::new(CheckerboardNode::new(size_x, size_y, color1, color2, properties)),
+            NodeParams::Lines { scale, thickness, position, orientation, color1, color2 } => Box::new(LinesNode {
+                scale, thickness, position, orientation, color1, color2, properties,
+            }),
+            NodeParams::Blend { option } => Box::new(BlendNode::new(option)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use texture_generators::GeneratorProperties;
+
+    use crate::templates::NodeTemplate;
+
+    #[test]
+    fn all_kinds() {
+        let kinds = NodeTemplate::all_kinds();
+        assert_eq!(5, kinds.len());
+        for kind in [NodeTemplate::SolidColor, NodeTemplate::Gradient, NodeTemplate::Checkerboard, NodeTemplate::Lines, NodeTemplate::Blend] {
+            assert!(kinds.contains(&kind));
+        }
+        assert_eq!(4, kinds.iter().filter(|kind| kind.category() == "Generator nodes").count());
+    }
+
+    #[test]
+    fn build_default_nodes() {
+        for kind in NodeTemplate::all_kinds() {
+            let params = kind.default_params();
+            assert_eq!(kind, params.template());
+            let node = params.build_node(GeneratorProperties::default());
+            let expected_inputs = if kind == NodeTemplate::Blend { 2 } else { 0 };
+            assert_eq!(expected_inputs, node.inputs());
+        }
+    }
+}
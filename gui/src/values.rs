@@ -0,0 +1,158 @@
+use core::{Brightness, Color};
+use std::hash::Hash;
+
+use eframe::egui;
+use image::Rgb32FImage;
+use texture_generators::BlendOptions;
+
+/// The blend modes in the order they are listed in the combo box of a blend node.
+pub const BLEND_MODES: [&str; 6] = ["Add", "Subtract", "Multiply", "Mask", "Mask inverted", "Overlay"];
+
+/// The threshold a mask starts with when it is selected in the combo box.
+const DEFAULT_MASK_THRESHOLD: f32 = 0.5;
+
+/// Returns the index in `BLEND_MODES` of the selected blend mode.
+pub fn blend_mode_index(option: &BlendOptions) -> usize {
+    match option {
+        BlendOptions::Add => 0,
+        BlendOptions::Subtract => 1,
+        BlendOptions::Multiply => 2,
+        BlendOptions::Mask(..) => 3,
+        BlendOptions::MaskInverted(..) => 4,
+        BlendOptions::Overlay => 5,
+    }
+}
+
+/// Selects the blend mode at the given index in `BLEND_MODES`, as chosen in the combo box.
+/// The threshold and brightness are kept when switching between the mask modes. Unknown indices are ignored.
+pub fn select_blend_mode(option: &mut BlendOptions, index: usize) {
+    let (threshold, brightness) = match option {
+        BlendOptions::Mask(threshold, brightness) | BlendOptions::MaskInverted(threshold, brightness) => (*threshold, *brightness),
+        _ => (DEFAULT_MASK_THRESHOLD, Brightness::default()),
+    };
+    *option = match index {
+        0 => BlendOptions::Add,
+        1 => BlendOptions::Subtract,
+        2 => BlendOptions::Multiply,
+        3 => BlendOptions::Mask(threshold, brightness),
+        4 => BlendOptions::MaskInverted(threshold, brightness),
+        5 => BlendOptions::Overlay,
+        _ => return,
+    };
+}
+
+/// Returns the threshold of a mask, which is edited with a drag value below the combo box.
+/// Returns `None` for the modes that have no threshold, in which case the drag value is hidden.
+pub fn mask_threshold(option: &mut BlendOptions) -> Option<&mut f32> {
+    match option {
+        BlendOptions::Mask(threshold, _) | BlendOptions::MaskInverted(threshold, _) => Some(threshold),
+        _ => None,
+    }
+}
+
+/// Draws the combo box of a blend node, and a drag value for the threshold below it when a mask mode is selected.
+/// Returns whether the blend mode or threshold changed.
+pub fn blend_mode_widget(ui: &mut egui::Ui, id_source: impl Hash, option: &mut BlendOptions) -> bool {
+    let mut index = blend_mode_index(option);
+    let mut changed = egui::ComboBox::from_id_source(id_source)
+        .show_index(ui, &mut index, BLEND_MODES.len(), |i| BLEND_MODES[i])
+        .changed();
+    if changed {
+        select_blend_mode(option, index);
+    }
+    if let Some(threshold) = mask_threshold(option) {
+        let drag = egui::DragValue::new(threshold).speed(0.01).clamp_range(0.0..=1.0).prefix("threshold ");
+        changed |= ui.add(drag).changed();
+    }
+    changed
+}
+
+/// Draws a color picker for a color, followed by numeric entries that also accept HDR values above 1.
+/// Colors outside the range of the picker are marked, as they are clipped when exported to an 8-bit PNG.
+/// Returns whether the color changed.
+pub fn color_widget(ui: &mut egui::Ui, color: &mut Color) -> bool {
+    ui.horizontal(|ui| {
+        let mut changed = false;
+        if ui.color_edit_button_rgb(&mut color.0).changed() {
+            clamp_color(color);
+            changed = true;
+        }
+        for value in color.0.iter_mut() {
+            changed |= ui.add(egui::DragValue::new(value).speed(0.01).clamp_range(0.0..=f32::INFINITY).max_decimals(3)).changed();
+        }
+        if is_out_of_range(color) {
+            ui.colored_label(ui.visuals().warn_fg_color, "HDR").on_hover_text("Values above 1 are clipped in 8-bit exports");
+        }
+        changed
+    }).inner
+}
+
+/// Clamps a color to the 0..1 range, which the color picker writes back after every edit.
+/// Values above 1 can still be entered with the numeric HDR entry.
+pub fn clamp_color(color: &mut Color) {
+    color.0 = color.0.map(|v| v.clamp(0.0, 1.0));
+}
+
+/// Checks whether any channel of a color lies outside of the 0..1 range that the color picker can show.
+pub fn is_out_of_range(color: &Color) -> bool {
+    color.0.iter().any(|v| !(0.0..=1.0).contains(v))
+}
+
+/// Returns the fraction of pixels of a result with a channel outside of the 0..1 range, shown as a clipping indicator on the node.
+/// Such values are clipped when the result is exported to an 8-bit PNG, so any detail above 1 is lost.
+pub fn clipped_fraction(image: &Rgb32FImage) -> f32 {
+    let pixel_count = image.width() as usize * image.height() as usize;
+    if pixel_count == 0 {
+        return 0.0;
+    }
+    image.pixels().filter(|pix| is_out_of_range(pix)).count() as f32 / pixel_count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use core::Brightness;
+
+    use image::{Rgb, Rgb32FImage};
+    use texture_generators::BlendOptions;
+
+    use crate::values::{blend_mode_index, clamp_color, clipped_fraction, is_out_of_range, mask_threshold, select_blend_mode,
+        BLEND_MODES};
+
+    #[test]
+    fn select_every_mode() {
+        let mut option = BlendOptions::Add;
+        for index in 0..BLEND_MODES.len() {
+            select_blend_mode(&mut option, index);
+            assert_eq!(index, blend_mode_index(&option));
+        }
+        select_blend_mode(&mut option, BLEND_MODES.len());
+        assert_eq!(BLEND_MODES.len() - 1, blend_mode_index(&option));
+    }
+
+    #[test]
+    fn mask_keeps_threshold() {
+        let mut option = BlendOptions::Multiply;
+        assert!(mask_threshold(&mut option).is_none());
+        select_blend_mode(&mut option, 3);
+        *mask_threshold(&mut option).unwrap() = 0.8;
+        select_blend_mode(&mut option, 4);
+        assert!(matches!(option, BlendOptions::MaskInverted(threshold, Brightness::Average) if threshold == 0.8));
+    }
+
+    #[test]
+    fn clamp_hdr_color() {
+        let mut color = Rgb([1.5, 0.5, -0.2]);
+        assert!(is_out_of_range(&color));
+        clamp_color(&mut color);
+        assert_eq!(Rgb([1.0, 0.5, 0.0]), color);
+        assert!(!is_out_of_range(&color));
+    }
+
+    #[test]
+    fn clipped_pixels() {
+        let mut image = Rgb32FImage::from_pixel(2, 2, Rgb([0.5, 1.0, 0.0]));
+        assert_eq!(0.0, clipped_fraction(&image));
+        image.put_pixel(1, 0, Rgb([2.0, 1.0, 1.0]));
+        assert_eq!(0.25, clipped_fraction(&image));
+    }
+}
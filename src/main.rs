@@ -1,8 +1,8 @@
-use core::Gradient;
+use core::{Brightness, Gradient};
 
-use graph::{TextureGraph, Node, NodeIndex};
+use graph::{GraphError, TextureGraph, Node, NodeIndex};
 use image::{RgbImage, Rgb, Rgb32FImage, buffer::ConvertBuffer};
-use texture_generators::{GradientNode, CheckerboardNode, LinesNode, LinesPosition, BlendNode, SolidColorNode, GeneratorProperties};
+use texture_generators::{GradientNode, CheckerboardNode, LinesNode, LinesPosition, LineOrientation, BlendNode, SolidColorNode, GeneratorProperties};
 
 fn create_graph(nodes: Vec<Node<Rgb32FImage>>) -> (TextureGraph<Rgb32FImage>, Vec<NodeIndex>) {
     let mut graph = TextureGraph::<Rgb32FImage>::new();
@@ -13,73 +13,43 @@ fn create_graph(nodes: Vec<Node<Rgb32FImage>>) -> (TextureGraph<Rgb32FImage>, Ve
     (graph, indices)
 }
 
-fn add_edges(graph: &mut TextureGraph<Rgb32FImage>, indices: &Vec<NodeIndex>, edge_indices: Vec<(usize, usize, usize)>) -> Result<(), String> {
+fn add_edges(graph: &mut TextureGraph<Rgb32FImage>, indices: &[NodeIndex], edge_indices: Vec<(usize, usize, usize)>) -> Result<(), GraphError> {
     edge_indices.iter().map(|(a, b, target)| (indices[*a], indices[*b], target))
-        .map(|(e1, e2, target)| graph.add_edge(e1, e2, *target)).collect()
-    
+        .try_for_each(|(e1, e2, target)| graph.add_edge(e1, e2, *target))
 }
 
-fn main() -> () {
+fn main() {
     let nodes = vec![
         Node::new(String::from("Solid"), Box::new(SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::default()})),
         Node::new(String::from("GradientHorizontal"), 
-            Box::new(GradientNode{gradient:Gradient{
+            Box::new(GradientNode::new(Gradient{
                 start: Rgb([0.0, 0.0, 0.0]),
                 end: Rgb([0.0, 1.0, 0.0])
-            }, direction: texture_generators::GradientNodeDirection::HORIZONTAL,
-            properties: GeneratorProperties::default()
-        })),
+            }, texture_generators::GradientNodeDirection::HORIZONTAL, GeneratorProperties::default()))),
         Node::new(String::from("GradientVertical"), 
-            Box::new(GradientNode{gradient:Gradient{
+            Box::new(GradientNode::new(Gradient{
                 start: Rgb([0.0, 0.0, 0.0]),
                 end: Rgb([1.0, 0.0, 0.0])
-            }, direction: texture_generators::GradientNodeDirection::VERTICAL, 
-            properties: GeneratorProperties::default()
-        })),
+            }, texture_generators::GradientNodeDirection::VERTICAL, GeneratorProperties::default()))),
         Node::new(String::from("GradientRadial"), 
-            Box::new(GradientNode{gradient:Gradient{
+            Box::new(GradientNode::new(Gradient{
                 start: Rgb([0.0, 0.0, 0.0]),
                 end: Rgb([1.0, 0.0, 0.0])
-            }, direction: texture_generators::GradientNodeDirection::RADIAL, 
-            properties: GeneratorProperties::default()
-        })),
+            }, texture_generators::GradientNodeDirection::RADIAL, GeneratorProperties::default()))),
         Node::new(String::from("CheckerboardS1"),
-            Box::new(CheckerboardNode{
-                size_x: 1,
-                size_y: 1,
-                color1: Rgb([0.0, 0.0, 0.0]),
-                color2: Rgb([1.0, 1.0, 1.0]), 
-                properties: GeneratorProperties::default()
-            })),
+            Box::new(CheckerboardNode::new(1, 1, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::default()))),
         Node::new(String::from("CheckerboardS2"),
-            Box::new(CheckerboardNode{
-                size_x: 2,
-                size_y: 2,
-                color1: Rgb([0.0, 0.0, 0.0]),
-                color2: Rgb([0.0, 1.0, 1.0]),
-                properties: GeneratorProperties::default()
-            })),
+            Box::new(CheckerboardNode::new(2, 2, Rgb([0.0, 0.0, 0.0]), Rgb([0.0, 1.0, 1.0]), GeneratorProperties::default()))),
         Node::new(String::from("CheckerboardS3"),
-            Box::new(CheckerboardNode{
-                size_x: 3,
-                size_y: 3,
-                color1: Rgb([0.0, 0.0, 0.0]),
-                color2: Rgb([1.0, 0.0, 0.0]),
-                properties: GeneratorProperties::default()
-            })),
+            Box::new(CheckerboardNode::new(3, 3, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 0.0, 0.0]), GeneratorProperties::default()))),
         Node::new(String::from("CheckerboardS1-3"),
-            Box::new(CheckerboardNode{
-                size_x: 1,
-                size_y: 3,
-                color1: Rgb([0.0, 0.0, 0.0]),
-                color2: Rgb([0.0, 1.0, 0.0]),
-                properties: GeneratorProperties::default()
-            })),
+            Box::new(CheckerboardNode::new(1, 3, Rgb([0.0, 0.0, 0.0]), Rgb([0.0, 1.0, 0.0]), GeneratorProperties::default()))),
         Node::new(String::from("LineStart"),
             Box::new(LinesNode {
                 scale:1, 
                 thickness: 0.4, 
-                position: LinesPosition::Start, 
+                position: LinesPosition::Start,
+                orientation: LineOrientation::Horizontal,
                 color1: Rgb([0.0, 0.0, 0.0]), 
                 color2: Rgb([1.0, 1.0, 1.0]),
                 properties: GeneratorProperties::default()
@@ -88,7 +58,8 @@ fn main() -> () {
             Box::new(LinesNode {
                 scale:1, 
                 thickness: 0.4, 
-                position: LinesPosition::Middle, 
+                position: LinesPosition::Middle,
+                orientation: LineOrientation::Horizontal,
                 color1: Rgb([0.0, 0.0, 0.0]), 
                 color2: Rgb([1.0, 1.0, 1.0]),
                 properties: GeneratorProperties::default()
@@ -97,7 +68,8 @@ fn main() -> () {
             Box::new(LinesNode {
                 scale:1, 
                 thickness: 0.4, 
-                position: LinesPosition::End, 
+                position: LinesPosition::End,
+                orientation: LineOrientation::Horizontal,
                 color1: Rgb([0.0, 0.0, 0.0]), 
                 color2: Rgb([1.0, 1.0, 1.0]),
                 properties: GeneratorProperties::default()
@@ -106,25 +78,18 @@ fn main() -> () {
             Box::new(LinesNode {
                 scale:4, 
                 thickness: 0.2, 
-                position: LinesPosition::Start, 
+                position: LinesPosition::Start,
+                orientation: LineOrientation::Horizontal,
                 color1: Rgb([0.0, 0.0, 0.0]), 
                 color2: Rgb([1.0, 1.0, 1.0]),
                 properties: GeneratorProperties::default()
             })),
         Node::new(String::from("BasicBlend"),
-            Box::new(BlendNode {
-                option: texture_generators::BlendOptions::Multiply
-            })),
+            Box::new(BlendNode::new(texture_generators::BlendOptions::Multiply))),
         Node::new(String::from("GrayGradient"),
-            Box::new(GradientNode {
-                gradient: Gradient{start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0])},
-                direction: texture_generators::GradientNodeDirection::HORIZONTAL,
-                properties: GeneratorProperties::default()
-            },)),
+            Box::new(GradientNode::new(Gradient{start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0])}, texture_generators::GradientNodeDirection::HORIZONTAL, GeneratorProperties::default()),)),
         Node::new(String::from("MaskBlend"),
-            Box::new(BlendNode {
-                option: texture_generators::BlendOptions::Mask(0.4)
-            }))
+            Box::new(BlendNode::new(texture_generators::BlendOptions::Mask(0.4, Brightness::Average))))
     ];
     let (mut graph, indices) = create_graph(nodes);
     let edges = vec![
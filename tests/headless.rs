@@ -0,0 +1,23 @@
+//! Exercises graph generation through the root package without depending on the GUI crate.
+use core::Gradient;
+
+use graph::{TextureGraph, Node};
+use image::{Rgb, Rgb32FImage};
+use texture_generators::{BlendNode, BlendOptions, GeneratorProperties, GradientNode, GradientNodeDirection, SolidColorNode};
+
+#[test]
+fn generate_graph_headless() {
+    let mut graph = TextureGraph::<Rgb32FImage>::new();
+    let solid = graph.add_node(Node::new(String::from("Solid"),
+        Box::new(SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::default()})));
+    let gradient = graph.add_node(Node::new(String::from("Gradient"),
+        Box::new(GradientNode::new(Gradient{start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0])}, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default()))));
+    let blend = graph.add_node(Node::new(String::from("Blend"),
+        Box::new(BlendNode::new(BlendOptions::Multiply))));
+    graph.add_edge(solid, blend, 0).unwrap();
+    graph.add_edge(gradient, blend, 1).unwrap();
+    assert!(graph.generate_graph().is_ok());
+    let result = graph.get_generated_node(&blend).unwrap();
+    assert_eq!(128, result.width());
+    assert!(result.pixels().all(|pix| pix.0[1] == 0.0 && pix.0[2] == 0.0));
+}
@@ -0,0 +1,87 @@
+use core::{average_color, Color};
+
+use image::{Rgb, Rgb32FImage};
+use rustfft::{FftPlanner, num_complex::Complex};
+
+/// Folds an FFT bin index to the frequency it represents, in cycles per image.
+fn fold(k: usize, n: usize) -> usize {
+    if k > n / 2 { n - k } else { k }
+}
+
+/// Finds the strongest repeating pattern in an image, which can be used to verify the scale of generators.
+/// Runs a 2D FFT on the brightness of the image and returns the frequency of the highest non-DC peak,
+/// in cycles per image in the horizontal and vertical direction.
+pub fn dominant_frequency(img: &Rgb32FImage) -> (f32, f32) {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+    if width == 0 || height == 0 {
+        return (0.0, 0.0);
+    }
+    let mut data: Vec<Complex<f32>> = img.pixels()
+        .map(|pix| Complex::new(average_color(pix), 0.0))
+        .collect();
+    let mut planner = FftPlanner::new();
+    let row_fft = planner.plan_fft_forward(width);
+    for row in data.chunks_exact_mut(width) {
+        row_fft.process(row);
+    }
+    let column_fft = planner.plan_fft_forward(height);
+    let mut column = vec![Complex::new(0.0, 0.0); height];
+    for x in 0..width {
+        for y in 0..height {
+            column[y] = data[y * width + x];
+        }
+        column_fft.process(&mut column);
+        for y in 0..height {
+            data[y * width + x] = column[y];
+        }
+    }
+    let mut peak = (0, 0);
+    let mut peak_magnitude = 0.0;
+    for y in 0..height {
+        for x in 0..width {
+            if x == 0 && y == 0 {
+                continue
+            }
+            let magnitude = data[y * width + x].norm_sqr();
+            if magnitude > peak_magnitude {
+                peak_magnitude = magnitude;
+                peak = (x, y);
+            }
+        }
+    }
+    (fold(peak.0, width) as f32, fold(peak.1, height) as f32)
+}
+
+/// Computes the mean color of all pixels of an image. An empty image has a black mean.
+pub fn mean_color(img: &Rgb32FImage) -> Color {
+    let count = (img.width() as u64 * img.height() as u64).max(1) as f64;
+    let sum = img.pixels().fold([0.0f64; 3], |acc, pix| [0, 1, 2].map(|c| acc[c] + pix[c] as f64));
+    Rgb(sum.map(|v| (v / count) as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::TextureTransformer;
+    use image::Rgb;
+
+    use crate::{dominant_frequency, GeneratorProperties, LinesNode, LinesPosition, LineOrientation, CheckerboardNode};
+
+    #[test]
+    fn test_frequency_lines() {
+        let node = LinesNode {
+            scale: 8, thickness: 0.5, position: LinesPosition::Start, orientation: LineOrientation::Horizontal,
+            color1: Rgb([0.0, 0.0, 0.0]), color2: Rgb([1.0, 1.0, 1.0]),
+            properties: GeneratorProperties::default()
+        };
+        let image = node.generate(vec![]);
+        assert_eq!((0.0, 8.0), dominant_frequency(&image));
+    }
+
+    #[test]
+    fn test_frequency_checkerboard() {
+        let node = CheckerboardNode::new(3, 0, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::default());
+        let image = node.generate(vec![]);
+        assert_eq!((2.0, 0.0), dominant_frequency(&image));
+    }
+}
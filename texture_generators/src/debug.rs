@@ -0,0 +1,84 @@
+use core::color_to_hex;
+use image::{Rgb32FImage, imageops::{self, FilterType}};
+
+use crate::mean_color;
+
+/// Converts a channel value in the range 0..1 to a byte.
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Renders an image as 24-bit ANSI colored text, to quickly inspect a texture in a terminal.
+/// The image is downsampled to `cols` characters wide. Each character is a half block,
+/// where the foreground color is the upper pixel and the background color the lower pixel,
+/// which keeps the aspect ratio of the image intact.
+pub fn to_ansi(img: &Rgb32FImage, cols: u32) -> String {
+    if cols == 0 || img.width() == 0 || img.height() == 0 {
+        return String::new();
+    }
+    let pixel_rows = ((cols as f32 * img.height() as f32 / img.width() as f32).round() as u32).max(1);
+    let resized = imageops::resize(img, cols, pixel_rows, FilterType::Triangle);
+    let mut output = String::new();
+    for row in (0..pixel_rows).step_by(2) {
+        for x in 0..cols {
+            let [r1, g1, b1] = resized.get_pixel(x, row).0.map(to_byte);
+            output.push_str(&format!("\x1b[38;2;{};{};{}m", r1, g1, b1));
+            if row + 1 < pixel_rows {
+                let [r2, g2, b2] = resized.get_pixel(x, row + 1).0.map(to_byte);
+                output.push_str(&format!("\x1b[48;2;{};{};{}m", r2, g2, b2));
+            } else {
+                output.push_str("\x1b[49m");
+            }
+            output.push('▀');
+        }
+        output.push_str("\x1b[0m\n");
+    }
+    output
+}
+
+/// Summarizes an image as its dimensions and average color, such as `128x128, average #808080`.
+/// Meant for `TextureGraph::debug_dump_with`, to describe results without including every pixel.
+pub fn image_summary(image: &Rgb32FImage) -> String {
+    format!("{}x{}, average {}", image.width(), image.height(), color_to_hex(&mean_color(image)))
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::TextureTransformer;
+    use image::Rgb;
+
+    use crate::{image_summary, to_ansi, SolidColorNode, GeneratorProperties};
+
+    #[test]
+    fn test_ansi_square() {
+        let node = SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::default()};
+        let image = node.generate(vec![]);
+        let output = to_ansi(&image, 16);
+        assert_eq!(8, output.lines().count());
+        assert!(output.lines().all(|line| line.chars().filter(|&c| c == '▀').count() == 16));
+        assert!(output.contains("\x1b[38;2;255;0;0m"));
+    }
+
+    #[test]
+    fn test_ansi_aspect_ratio() {
+        let node = SolidColorNode{color: Rgb([0.0, 0.0, 0.0]), properties: GeneratorProperties { width: 128, height: 64 }};
+        let image = node.generate(vec![]);
+        assert_eq!(8, to_ansi(&image, 32).lines().count());
+        let node = SolidColorNode{color: Rgb([0.0, 0.0, 0.0]), properties: GeneratorProperties { width: 64, height: 128 }};
+        let image = node.generate(vec![]);
+        assert_eq!(16, to_ansi(&image, 16).lines().count());
+    }
+
+    #[test]
+    fn test_image_summary() {
+        let node = SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties { width: 64, height: 32 }};
+        let mut image = node.generate(vec![]);
+        assert_eq!("64x32, average #ff0000", image_summary(&image));
+        for x in 0..32 {
+            for y in 0..32 {
+                image.put_pixel(x, y, Rgb([0.0, 0.0, 1.0]));
+            }
+        }
+        assert_eq!("64x32, average #800080", image_summary(&image));
+    }
+}
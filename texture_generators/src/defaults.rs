@@ -0,0 +1,345 @@
+//! Constructors for every node with sensible parameters, so a node finder can add any kind of node by name.
+use core::Gradient;
+use std::path::PathBuf;
+
+use graph::TextureTransformer;
+use image::{Rgb, Rgb32FImage};
+
+use crate::{AverageColorNode, BlendNode, BlendOptions, BloomNode, BoxBlurNode, CheckerboardNode, CombineNode, CombineOp, ConstantNode, CropNode, CrossHatchNode, CurveNode,
+    DistanceMetric, FloodRecolorNode, DitherMethod, DitherNode, EdgeMode, GeneratorProperties, GradientFromInputsNode, GradientNode, GradientNodeDirection, GradientRepeat, HexagonNode,
+    ImageFileNode, LerpNode, LineOrientation, LinesNode, LinesPosition, LuminanceToMaskNode, MaskOp, MaskOpNode, MaxNode, MinNode,
+    MotionBlurNode, NoiseWarpNode, NormalBlend, NormalBlendNode, PadNode, PerlinNode, ProbeNode, RingsNode, ScaleFilter, ScaleNode, SharpenNode,
+    SolidColorNode, TurbulenceNode, ViewTransformNode, VoronoiNode, VoronoiOutput};
+
+/// A node that can be created without any parameters other than the size of the generated images.
+pub trait DefaultNode {
+    /// Creates the node with default parameters. Generators produce images of the given properties, transformers ignore them.
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>>;
+}
+
+/// Creates a node with default parameters, as registered in `default_nodes`.
+pub type NodeConstructor = fn(GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>>;
+
+const BLACK: Rgb<f32> = Rgb([0.0, 0.0, 0.0]);
+const WHITE: Rgb<f32> = Rgb([1.0, 1.0, 1.0]);
+
+fn black_to_white() -> Gradient {
+    Gradient { start: BLACK, end: WHITE }
+}
+
+impl DefaultNode for SolidColorNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(SolidColorNode { color: WHITE, properties })
+    }
+}
+
+impl DefaultNode for ConstantNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(ConstantNode { value: 0.5, properties })
+    }
+}
+
+impl DefaultNode for GradientNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(GradientNode::new(black_to_white(), GradientNodeDirection::HORIZONTAL, properties))
+    }
+}
+
+impl DefaultNode for GradientFromInputsNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(GradientFromInputsNode { direction: GradientNodeDirection::HORIZONTAL, repeat: GradientRepeat::Clamp, repeats: 1.0, properties })
+    }
+}
+
+impl DefaultNode for CheckerboardNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(CheckerboardNode::new(1, 1, BLACK, WHITE, properties))
+    }
+}
+
+impl DefaultNode for LinesNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(LinesNode {
+            scale: 4,
+            thickness: 0.5,
+            position: LinesPosition::Start,
+            orientation: LineOrientation::Horizontal,
+            color1: BLACK,
+            color2: WHITE,
+            properties,
+        })
+    }
+}
+
+impl DefaultNode for ImageFileNode {
+    /// Creates a node without a path, which generates the magenta placeholder until a path is set.
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(ImageFileNode { path: PathBuf::new(), properties })
+    }
+}
+
+impl DefaultNode for RingsNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(RingsNode { count: 4, center: (0.5, 0.5), color1: BLACK, color2: WHITE, properties })
+    }
+}
+
+impl DefaultNode for HexagonNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(HexagonNode { size: 4.0, color1: BLACK, color2: WHITE, properties })
+    }
+}
+
+impl DefaultNode for CrossHatchNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(CrossHatchNode { spacing: 16, thickness: 0.1, angle: std::f32::consts::FRAC_PI_4, color1: WHITE, color2: BLACK, properties })
+    }
+}
+
+impl DefaultNode for PerlinNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(PerlinNode { scale: 8, seed: 0, tileable: true, gradient: black_to_white(), properties })
+    }
+}
+
+impl DefaultNode for TurbulenceNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(TurbulenceNode { scale: 8, octaves: 4, lacunarity: 2.0, gain: 0.5, seed: 0, gradient: black_to_white(), properties })
+    }
+}
+
+impl DefaultNode for VoronoiNode {
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(VoronoiNode {
+            scale: 8,
+            seed: 0,
+            metric: DistanceMetric::Euclidean,
+            output: VoronoiOutput::Distance,
+            gradient: black_to_white(),
+            properties,
+        })
+    }
+}
+
+impl DefaultNode for NoiseWarpNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(NoiseWarpNode { scale: 8, strength: 4.0, seed: 0 })
+    }
+}
+
+impl DefaultNode for BlendNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(BlendNode::new(BlendOptions::Multiply))
+    }
+}
+
+impl DefaultNode for CurveNode {
+    /// Creates a node with the identity curve, which leaves its input unchanged.
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(CurveNode { points: vec![(0.0, 0.0), (1.0, 1.0)] })
+    }
+}
+
+impl DefaultNode for MaskOpNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(MaskOpNode { op: MaskOp::And })
+    }
+}
+
+impl DefaultNode for ViewTransformNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(ViewTransformNode { offset: (0.0, 0.0), zoom: 1.0, edge_mode: EdgeMode::Wrap })
+    }
+}
+
+impl DefaultNode for SharpenNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(SharpenNode { amount: 1.0, edge_mode: EdgeMode::Clamp })
+    }
+}
+
+impl DefaultNode for MotionBlurNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(MotionBlurNode { angle: 0.0, length: 5, edge_mode: EdgeMode::Clamp })
+    }
+}
+
+impl DefaultNode for BoxBlurNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(BoxBlurNode { radius: 2, edge_mode: EdgeMode::Clamp })
+    }
+}
+
+impl DefaultNode for DitherNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(DitherNode { levels: 2, method: DitherMethod::Ordered })
+    }
+}
+
+impl DefaultNode for ScaleNode {
+    /// Creates a node that scales its input to the size of the properties.
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        let (width, height) = properties.dimensions();
+        Box::new(ScaleNode { width, height, filter: ScaleFilter::Bilinear, edge_mode: EdgeMode::Clamp })
+    }
+}
+
+impl DefaultNode for MinNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(MinNode {})
+    }
+}
+
+impl DefaultNode for MaxNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(MaxNode {})
+    }
+}
+
+impl DefaultNode for LerpNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(LerpNode { t: 0.5 })
+    }
+}
+
+impl DefaultNode for CombineNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(CombineNode { count: 3, op: CombineOp::Average })
+    }
+}
+
+impl DefaultNode for LuminanceToMaskNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(LuminanceToMaskNode { invert: false })
+    }
+}
+
+impl DefaultNode for CropNode {
+    /// Creates a node that crops out the top-left quarter of an input of the size of the properties.
+    fn default_node(properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        let (width, height) = properties.dimensions();
+        Box::new(CropNode { x: 0, y: 0, width: (width / 2).max(1), height: (height / 2).max(1) })
+    }
+}
+
+impl DefaultNode for PadNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(PadNode { left: 8, right: 8, top: 8, bottom: 8, fill: BLACK })
+    }
+}
+
+impl DefaultNode for NormalBlendNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(NormalBlendNode { method: NormalBlend::ReorientedNormalMapping })
+    }
+}
+
+impl DefaultNode for BloomNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(BloomNode { threshold: 0.8, radius: 4.0, intensity: 1.0 })
+    }
+}
+
+impl DefaultNode for FloodRecolorNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(FloodRecolorNode { tolerance: 0.1, replacement: WHITE, seed: (0, 0) })
+    }
+}
+
+impl DefaultNode for AverageColorNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(AverageColorNode {})
+    }
+}
+
+impl DefaultNode for ProbeNode {
+    fn default_node(_properties: GeneratorProperties) -> Box<dyn TextureTransformer<Rgb32FImage>> {
+        Box::new(ProbeNode {})
+    }
+}
+
+/// Returns the name and constructor of every node that can be created with default parameters, in the order of a node finder.
+/// Nodes built around a closure, such as `PixelMapNode`, have no sensible default and are not included.
+pub fn default_nodes() -> Vec<(&'static str, NodeConstructor)> {
+    vec![
+        ("Solid color", SolidColorNode::default_node),
+        ("Constant", ConstantNode::default_node),
+        ("Gradient", GradientNode::default_node),
+        ("Gradient from inputs", GradientFromInputsNode::default_node),
+        ("Checkerboard", CheckerboardNode::default_node),
+        ("Lines", LinesNode::default_node),
+        ("Image file", ImageFileNode::default_node),
+        ("Rings", RingsNode::default_node),
+        ("Hexagons", HexagonNode::default_node),
+        ("Crosshatch", CrossHatchNode::default_node),
+        ("Perlin noise", PerlinNode::default_node),
+        ("Turbulence", TurbulenceNode::default_node),
+        ("Voronoi", VoronoiNode::default_node),
+        ("Noise warp", NoiseWarpNode::default_node),
+        ("Blend", BlendNode::default_node),
+        ("Curve", CurveNode::default_node),
+        ("Mask operation", MaskOpNode::default_node),
+        ("View transform", ViewTransformNode::default_node),
+        ("Sharpen", SharpenNode::default_node),
+        ("Motion blur", MotionBlurNode::default_node),
+        ("Box blur", BoxBlurNode::default_node),
+        ("Dither", DitherNode::default_node),
+        ("Scale", ScaleNode::default_node),
+        ("Min", MinNode::default_node),
+        ("Max", MaxNode::default_node),
+        ("Lerp", LerpNode::default_node),
+        ("Combine", CombineNode::default_node),
+        ("Luminance to mask", LuminanceToMaskNode::default_node),
+        ("Crop", CropNode::default_node),
+        ("Pad", PadNode::default_node),
+        ("Normal blend", NormalBlendNode::default_node),
+        ("Bloom", BloomNode::default_node),
+        ("Flood recolor", FloodRecolorNode::default_node),
+        ("Average color", AverageColorNode::default_node),
+        ("Probe", ProbeNode::default_node),
+    ]
+}
+
+/// Creates the node registered under the given name in `default_nodes`, or None if no node has that name.
+pub fn default_node_by_name(name: &str, properties: GeneratorProperties) -> Option<Box<dyn TextureTransformer<Rgb32FImage>>> {
+    default_nodes().into_iter()
+        .find(|(node_name, _)| *node_name == name)
+        .map(|(_, constructor)| constructor(properties))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use image::Rgb32FImage;
+
+    use crate::{default_node_by_name, default_nodes, GeneratorProperties};
+
+    #[test]
+    fn registry_inputs() {
+        let properties = GeneratorProperties { width: 16, height: 8 };
+        let nodes = default_nodes();
+        let names: HashSet<_> = nodes.iter().map(|(name, _)| *name).collect();
+        assert_eq!(nodes.len(), names.len());
+        let input = Rgb32FImage::new(16, 8);
+        for (name, constructor) in nodes {
+            let node = constructor(properties.clone());
+            let expected = match name {
+                "Noise warp" | "Curve" | "View transform" | "Sharpen" | "Motion blur" | "Box blur" | "Dither" | "Scale" | "Luminance to mask" | "Crop"
+                    | "Pad" | "Bloom" | "Flood recolor" | "Average color" | "Probe" => 1,
+                "Gradient from inputs" | "Blend" | "Mask operation" | "Min" | "Max" | "Lerp" | "Normal blend" => 2,
+                "Combine" => 3,
+                _ => 0,
+            };
+            assert_eq!(expected, node.inputs(), "{} has {} inputs", name, node.inputs());
+            // Every default node can be generated from inputs of the size of the properties.
+            let inputs = vec![&input; expected];
+            assert!(node.is_valid(&inputs), "{} rejects its inputs", name);
+            if expected == 0 {
+                assert_eq!((16, 8), node.generate(inputs).dimensions());
+            }
+        }
+        assert_eq!(2, default_node_by_name("Blend", properties.clone()).unwrap().inputs());
+        assert!(default_node_by_name("Unknown", properties).is_none());
+    }
+}
@@ -0,0 +1,130 @@
+use core::Color;
+use std::path::Path;
+
+use image::{Rgb32FImage, RgbImage, ImageError, ImageFormat, Pixel};
+
+/// The file format used when saving a generated texture.
+pub enum ExportFormat {
+    /// An 8-bit PNG. Values are clamped to 0..1, and optionally encoded from linear to sRGB.
+    Png { srgb: bool },
+    /// A 32-bit floating point OpenEXR image, written without any conversion.
+    Exr,
+}
+
+/// Converts a linear channel value to the sRGB transfer curve.
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Composites an image over a solid background color.
+///
+/// Images have no alpha channel, so this uses a fixed convention for the coverage of a pixel: its brightest channel,
+/// clamped to 0..1. Black pixels are treated as transparent and fully replaced by the background, pixels with any channel
+/// at 1 or above are treated as opaque and kept, and everything in between is added to the background scaled by
+/// the remaining coverage. This flattens dark regions, such as the gaps of a pattern, onto the background.
+/// It is not a general alpha composite: a dark but intentional color is lightened the same way.
+pub fn composite_over(image: &Rgb32FImage, background: Color) -> Rgb32FImage {
+    Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+        let pix = image.get_pixel(x, y);
+        let [r, g, b] = pix.0.map(|v| v.clamp(0.0, 1.0));
+        let coverage = r.max(g).max(b);
+        pix.map2(&background, |c, bg| c + bg * (1.0 - coverage))
+    })
+}
+
+/// Saves a generated texture to disk in the given format.
+/// If a background color is given, the image is first composited over that color with [`composite_over`].
+pub fn save_texture(image: &Rgb32FImage, path: &Path, format: ExportFormat, background: Option<Color>) -> Result<(), ImageError> {
+    let composited;
+    let image = match background {
+        Some(color) => {
+            composited = composite_over(image, color);
+            &composited
+        },
+        None => image,
+    };
+    match format {
+        ExportFormat::Png { srgb } => {
+            let converted = RgbImage::from_fn(image.width(), image.height(), |x, y| {
+                image::Rgb(image.get_pixel(x, y).0.map(|v| {
+                    let v = v.clamp(0.0, 1.0);
+                    let v = if srgb { linear_to_srgb(v) } else { v };
+                    (v * 255.0).round() as u8
+                }))
+            });
+            converted.save_with_format(path, ImageFormat::Png)
+        },
+        ExportFormat::Exr => image.save_with_format(path, ImageFormat::OpenExr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::Gradient;
+
+    use graph::TextureTransformer;
+    use image::{Rgb, Rgb32FImage};
+
+    use crate::{composite_over, save_texture, ExportFormat, GradientNode, GradientNodeDirection, GeneratorProperties, CheckerboardNode};
+
+    #[test]
+    fn test_png_round_trip() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.2, 1.0]), end: Rgb([1.0, 0.8, 0.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties { width: 64, height: 32 });
+        let image = node.generate(vec![]);
+        let path = std::env::temp_dir().join("texture_generators_png_round_trip.png");
+        save_texture(&image, &path, ExportFormat::Png { srgb: false }, None).unwrap();
+        let loaded = image::open(&path).unwrap().to_rgb32f();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(64, loaded.width());
+        assert_eq!(32, loaded.height());
+        assert!(loaded.enumerate_pixels().all(|(x, y, pix)| {
+            pix.0.iter().zip(image.get_pixel(x, y).0).all(|(a, b)| (a - b).abs() <= 0.5 / 255.0 + 1e-6)
+        }));
+    }
+
+    #[test]
+    fn test_exr_round_trip() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([2.0, 1.0, 0.5]) }, GradientNodeDirection::VERTICAL, GeneratorProperties { width: 16, height: 16 });
+        let image = node.generate(vec![]);
+        let path = std::env::temp_dir().join("texture_generators_exr_round_trip.exr");
+        save_texture(&image, &path, ExportFormat::Exr, None).unwrap();
+        let loaded = image::open(&path).unwrap().to_rgb32f();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image, loaded);
+    }
+
+    #[test]
+    fn test_png_background() {
+        let node = CheckerboardNode::new(1, 1, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 0.0, 0.0]), GeneratorProperties { width: 16, height: 16 });
+        let image = node.generate(vec![]);
+        let path = std::env::temp_dir().join("texture_generators_png_background.png");
+        save_texture(&image, &path, ExportFormat::Png { srgb: false }, Some(Rgb([1.0, 1.0, 1.0]))).unwrap();
+        let loaded = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&Rgb([255, 255, 255]), loaded.get_pixel(0, 0));
+        assert_eq!(&Rgb([255, 255, 255]), loaded.get_pixel(15, 15));
+        assert_eq!(&Rgb([255, 0, 0]), loaded.get_pixel(15, 0));
+    }
+
+    #[test]
+    fn test_composite_coverage() {
+        let background = Rgb([0.2, 0.4, 0.6]);
+        let mut image = Rgb32FImage::new(5, 1);
+        image.put_pixel(1, 0, Rgb([1.0, 0.0, 0.0]));
+        image.put_pixel(2, 0, Rgb([0.5, 0.25, 0.0]));
+        image.put_pixel(3, 0, Rgb([2.0, 0.5, 0.0]));
+        image.put_pixel(4, 0, Rgb([-1.0, 0.0, 0.0]));
+        let composited = composite_over(&image, background);
+        // Black is fully transparent, a channel at 1 or above is fully opaque, the brightest channel is the coverage.
+        assert_eq!(&background, composited.get_pixel(0, 0));
+        assert_eq!(&Rgb([1.0, 0.0, 0.0]), composited.get_pixel(1, 0));
+        assert_eq!(&Rgb([0.6, 0.45, 0.3]), composited.get_pixel(2, 0));
+        assert_eq!(&Rgb([2.0, 0.5, 0.0]), composited.get_pixel(3, 0));
+        // Negative values count as no coverage, but are still added to the background.
+        assert_eq!(&Rgb([-0.8, 0.4, 0.6]), composited.get_pixel(4, 0));
+    }
+}
@@ -0,0 +1,48 @@
+use core::Brightness;
+
+use image::{Rgb, Rgb32FImage};
+
+/// Extracts a single value per pixel from an image, in row-major order, measuring each pixel with the given brightness.
+pub fn grayscale_of(image: &Rgb32FImage, brightness: Brightness) -> Vec<f32> {
+    image.pixels().map(|pix| brightness.of(pix)).collect()
+}
+
+/// Builds a gray image from one value per pixel in row-major order, as returned by `grayscale_of`.
+/// Panics if the amount of values does not match the dimensions.
+pub fn grayscale_to_rgb(values: &[f32], width: u32, height: u32) -> Rgb32FImage {
+    assert_eq!(width as usize * height as usize, values.len(), "Grayscale values do not match {}x{}", width, height);
+    Rgb32FImage::from_fn(width, height, |x, y| {
+        let value = values[(y * width + x) as usize];
+        Rgb([value, value, value])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{luminance, Brightness};
+
+    use image::{Rgb, Rgb32FImage};
+
+    use crate::{grayscale_of, grayscale_to_rgb};
+
+    #[test]
+    fn luminance_extraction() {
+        let image = Rgb32FImage::from_fn(4, 2, |x, y| Rgb([x as f32 / 4.0, y as f32 / 2.0, 0.5]));
+        let values = grayscale_of(&image, Brightness::Luminance);
+        assert_eq!(8, values.len());
+        assert!(image.pixels().zip(&values).all(|(pix, value)| luminance(pix) == *value));
+        let average = grayscale_of(&image, Brightness::Average);
+        assert!((average[5] - (0.25 + 0.5 + 0.5) / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn round_trip() {
+        let image = Rgb32FImage::from_fn(3, 5, |x, y| {
+            let value = (x + 3 * y) as f32 / 15.0;
+            Rgb([value, value, value])
+        });
+        let values = grayscale_of(&image, Brightness::Average);
+        let rebuilt = grayscale_to_rgb(&values, 3, 5);
+        assert!(image.pixels().zip(rebuilt.pixels()).all(|(a, b)| a.0.iter().zip(b.0).all(|(a, b)| (a - b).abs() < 1e-6)));
+    }
+}
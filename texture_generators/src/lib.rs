@@ -1,9 +1,49 @@
 mod nodes;
+mod analysis;
+mod debug;
+mod defaults;
+mod export;
+mod grayscale;
+mod parallel;
+pub mod templates;
 
 pub use crate::nodes::generators::GeneratorProperties;
 pub use crate::nodes::generators::SolidColorNode;
-pub use crate::nodes::generators::{GradientNode, GradientNodeDirection};
+pub use crate::nodes::generators::ConstantNode;
+pub use crate::nodes::generators::{GradientNode, GradientNodeDirection, GradientRepeat, GradientFromInputsNode};
 pub use crate::nodes::generators::CheckerboardNode;
-pub use crate::nodes::generators::{LinesNode, LinesPosition};
+pub use crate::nodes::generators::{LinesNode, LinesPosition, LineOrientation};
+pub use crate::nodes::generators::ImageFileNode;
+pub use crate::nodes::generators::RingsNode;
+pub use crate::nodes::generators::HexagonNode;
+pub use crate::nodes::generators::CrossHatchNode;
+pub use crate::nodes::noise::{PerlinNode, TurbulenceNode, NoiseWarpNode};
+pub use crate::nodes::noise::{VoronoiNode, DistanceMetric, VoronoiOutput};
 
-pub use crate::nodes::transformers::{BlendNode, BlendOptions};
\ No newline at end of file
+pub use crate::nodes::transformers::all_same_size;
+pub use crate::nodes::transformers::{EdgeMode, pixel_at, sample};
+pub use crate::nodes::transformers::{BlendNode, BlendOptions};
+pub use crate::nodes::transformers::CurveNode;
+pub use crate::nodes::transformers::{MaskOpNode, MaskOp};
+pub use crate::nodes::transformers::ViewTransformNode;
+pub use crate::nodes::transformers::SharpenNode;
+pub use crate::nodes::transformers::{MotionBlurNode, BoxBlurNode};
+pub use crate::nodes::transformers::{DitherNode, DitherMethod};
+pub use crate::nodes::transformers::{PixelMapNode, PixelMap2Node};
+pub use crate::nodes::transformers::{ScaleNode, ScaleFilter};
+pub use crate::nodes::transformers::{MinNode, MaxNode, LerpNode};
+pub use crate::nodes::transformers::{CombineNode, CombineOp};
+pub use crate::nodes::transformers::LuminanceToMaskNode;
+pub use crate::nodes::transformers::{CropNode, PadNode};
+pub use crate::nodes::transformers::{NormalBlendNode, NormalBlend};
+pub use crate::nodes::transformers::BloomNode;
+pub use crate::nodes::transformers::FloodRecolorNode;
+pub use crate::nodes::transformers::AverageColorNode;
+pub use crate::nodes::transformers::ProbeNode;
+
+pub use crate::debug::{to_ansi, image_summary};
+pub use crate::defaults::{DefaultNode, NodeConstructor, default_nodes, default_node_by_name};
+pub use crate::export::{save_texture, composite_over, ExportFormat};
+pub use crate::grayscale::{grayscale_of, grayscale_to_rgb};
+pub use crate::analysis::{dominant_frequency, mean_color};
+pub use crate::parallel::par_from_fn;
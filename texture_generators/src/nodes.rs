@@ -1,2 +1,29 @@
+/// Implements the `TextureTransformer` methods shared by every generator: it can be resized,
+/// and its output size comes from its `properties` rather than from its inputs.
+/// Used inside the `TextureTransformer` impl, together with `impl_resizable!` for the type.
+macro_rules! generator_size {
+    () => {
+        fn as_resizable(&mut self) -> Option<&mut dyn graph::Resizable> {
+            Some(self)
+        }
+
+        fn output_size(&self, _input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+            Some(self.properties.dimensions())
+        }
+    };
+}
+
+/// Implements `Resizable` for a generator that keeps its size in a `properties` field.
+macro_rules! impl_resizable {
+    ($node:ty) => {
+        impl graph::Resizable for $node {
+            fn set_size(&mut self, width: u32, height: u32) {
+                self.properties.resize(width, height);
+            }
+        }
+    };
+}
+
 pub mod generators;
-pub mod transformers;
\ No newline at end of file
+pub mod transformers;
+pub mod noise;
\ No newline at end of file
@@ -1,20 +1,68 @@
-use core::{Color, Gradient};
+use core::{color_to_hex, Color, Gradient};
+use std::borrow::Cow;
+use std::path::PathBuf;
 
-use graph::TextureTransformer;
-use image::{Rgb32FImage, ImageBuffer};
+use graph::{hash_debug, TextureTransformer};
+use image::{Rgb32FImage, ImageBuffer, Pixel, Rgb, imageops::{self, FilterType}};
 
+use crate::nodes::noise::lattice_hash;
+use crate::par_from_fn;
+
+#[derive(Clone, Debug)]
 pub struct GeneratorProperties {
     pub width: u32,
     pub height: u32
 }
 
 impl GeneratorProperties {
-    pub fn default() -> Self {
+    /// Creates the properties of a generator. Fails if the width or height is zero.
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("Invalid dimensions {}x{}", width, height));
+        }
+        Ok(GeneratorProperties { width, height })
+    }
+
+    /// Creates the properties of a square generator. Fails if the size is zero.
+    pub fn square(size: u32) -> Result<Self, String> {
+        Self::new(size, size)
+    }
+
+    /// The properties of a 256x256 generator.
+    pub fn p256() -> Self {
+        GeneratorProperties { width: 256, height: 256 }
+    }
+
+    /// The properties of a 512x512 generator.
+    pub fn p512() -> Self {
+        GeneratorProperties { width: 512, height: 512 }
+    }
+
+    /// The properties of a 1024x1024 generator.
+    pub fn p1024() -> Self {
+        GeneratorProperties { width: 1024, height: 1024 }
+    }
+
+    /// Changes the width and height of the generated image. Zero dimensions are clamped to 1 pixel when generating.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Returns the width and height of the generated image, clamped to at least 1 pixel.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width.max(1), self.height.max(1))
+    }
+}
+
+impl Default for GeneratorProperties {
+    fn default() -> Self {
         GeneratorProperties { width: 128, height: 128 }
     }
 }
 
 /// A node that generates a solid color.
+#[derive(Clone, Debug)]
 pub struct SolidColorNode {
     pub color: Color,
     pub properties: GeneratorProperties
@@ -22,15 +70,30 @@ pub struct SolidColorNode {
 
 impl TextureTransformer<Rgb32FImage> for SolidColorNode {
     fn generate(&self, _: Vec<&Rgb32FImage>) -> Rgb32FImage {
-        ImageBuffer::from_pixel(self.properties.width, self.properties.height, self.color)
+        let (width, height) = self.properties.dimensions();
+        ImageBuffer::from_pixel(width, height, self.color)
     }
 
     fn inputs(&self) -> usize {
         0
     }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("SolidColor({})", color_to_hex(&self.color))
+    }
 }
 
+impl_resizable!(SolidColorNode);
+
 /// The direction that a gradient moves to
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GradientNodeDirection {
     /// The gradient will move from left to right, and is constant in the vertical direction
     HORIZONTAL,
@@ -40,29 +103,101 @@ pub enum GradientNodeDirection {
     RADIAL
 }
 
+/// A node that fills an image with a single gray value, for feeding scalar parameters such as thresholds into other nodes.
+#[derive(Clone, Debug)]
+pub struct ConstantNode {
+    pub value: f32,
+    pub properties: GeneratorProperties,
+}
+
+impl TextureTransformer<Rgb32FImage> for ConstantNode {
+    fn generate(&self, _: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        ImageBuffer::from_pixel(width, height, Rgb([self.value, self.value, self.value]))
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("Constant({})", self.value)
+    }
+}
+
+impl_resizable!(ConstantNode);
+
+/// What a GradientNode does with positions past the end of the gradient.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GradientRepeat {
+    /// Uses the start or end color.
+    #[default]
+    Clamp,
+    /// Starts the gradient over, which gives sawtooth bands.
+    Repeat,
+    /// Runs the gradient backwards every other time, so the bands flow into each other.
+    Reflect,
+}
+
+impl GradientRepeat {
+    /// Maps a fraction along the gradient into the range 0..=1.
+    pub fn apply(&self, fraction: f32) -> f32 {
+        match self {
+            GradientRepeat::Clamp => fraction.clamp(0.0, 1.0),
+            GradientRepeat::Repeat => fraction.rem_euclid(1.0),
+            GradientRepeat::Reflect => {
+                let period = fraction.rem_euclid(2.0);
+                if period > 1.0 { 2.0 - period } else { period }
+            }
+        }
+    }
+}
+
+/// Describes how a gradient repeats, such as `clamp` or `reflect x2`.
+fn describe_repeat(repeat: GradientRepeat, repeats: f32) -> String {
+    format!("{} x{}", format!("{:?}", repeat).to_lowercase(), repeats)
+}
+
 /// A node that produces a smooth gradient in a specified direction
+#[derive(Clone, Debug)]
 pub struct GradientNode {
     pub gradient: Gradient,
     pub direction: GradientNodeDirection,
+    pub repeat: GradientRepeat,
+    /// How many times the gradient fits across the image. Positions past the first gradient are handled by `repeat`.
+    pub repeats: f32,
     pub properties: GeneratorProperties,
 }
 
+impl GradientNode {
+    /// Creates a gradient that spans the image once and is clamped past its ends.
+    pub fn new(gradient: Gradient, direction: GradientNodeDirection, properties: GeneratorProperties) -> Self {
+        GradientNode { gradient, direction, repeat: GradientRepeat::Clamp, repeats: 1.0, properties }
+    }
+}
+
 impl TextureTransformer<Rgb32FImage> for GradientNode {
     fn generate(&self, _: Vec<&Rgb32FImage>) -> Rgb32FImage {
-        let width = self.properties.width;
-        let height = self.properties.height;
+        let (width, height) = self.properties.dimensions();
         match self.direction {
             GradientNodeDirection::HORIZONTAL => ImageBuffer::from_fn(width, height, |x, _| {
-                self.gradient.get_color(x as f32 / width as f32)
+                self.gradient.get_color(self.repeat.apply(self.repeats * x as f32 / width as f32))
             }),
             GradientNodeDirection::VERTICAL => ImageBuffer::from_fn(width, height, |_, y| {
-                self.gradient.get_color(y as f32 / width as f32)
+                self.gradient.get_color(self.repeat.apply(self.repeats * y as f32 / width as f32))
             }),
-            GradientNodeDirection::RADIAL => ImageBuffer::from_fn(width, height, |x, y| {
+            GradientNodeDirection::RADIAL => par_from_fn(width, height, |x, y| {
                 let u = x as f32 / width as f32 - 0.5;
                 let v = y as f32 / height as f32 - 0.5;
                 let dist = (u*u+v*v).sqrt();
-                self.gradient.get_color(dist / 2.0_f32.sqrt())
+                self.gradient.get_color(self.repeat.apply(self.repeats * dist / 2.0_f32.sqrt()))
             }),
         }
     }
@@ -70,9 +205,66 @@ impl TextureTransformer<Rgb32FImage> for GradientNode {
     fn inputs(&self) -> usize {
         0
     }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        let direction = format!("{:?}", self.direction).to_lowercase();
+        format!("Gradient({}, {}, {})", direction, describe_repeat(self.repeat, self.repeats), self.gradient.describe())
+    }
+}
+
+impl_resizable!(GradientNode);
+
+/// A node that produces a gradient like a GradientNode, but reads its start and end color from the top-left pixel of its inputs,
+/// so the colors can be computed by other nodes, such as the average color of a texture.
+#[derive(Clone, Debug)]
+pub struct GradientFromInputsNode {
+    pub direction: GradientNodeDirection,
+    pub repeat: GradientRepeat,
+    /// How many times the gradient fits across the image. Positions past the first gradient are handled by `repeat`.
+    pub repeats: f32,
+    pub properties: GeneratorProperties,
 }
 
+impl TextureTransformer<Rgb32FImage> for GradientFromInputsNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let gradient = Gradient { start: *inputs[0].get_pixel(0, 0), end: *inputs[1].get_pixel(0, 0) };
+        GradientNode { repeat: self.repeat, repeats: self.repeats, ..GradientNode::new(gradient, self.direction.clone(), self.properties.clone()) }.generate(vec![])
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn input_names(&self) -> Vec<Cow<'static, str>> {
+        vec![Cow::Borrowed("start"), Cow::Borrowed("end")]
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && inputs.iter().all(|image| image.width() > 0 && image.height() > 0)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        let direction = format!("{:?}", self.direction).to_lowercase();
+        format!("GradientFromInputs({}, {})", direction, describe_repeat(self.repeat, self.repeats))
+    }
+}
+
+impl_resizable!(GradientFromInputsNode);
+
 /// A node that produces a checkerboard pattern.
+#[derive(Clone, Debug)]
 pub struct CheckerboardNode {
     /// The amount of tiles in the horizontal direction. The amount of tiles in the image is x+1
     pub size_x: usize,
@@ -82,17 +274,30 @@ pub struct CheckerboardNode {
     pub color1: Color,
     /// The color of the tiles not starting in the top-left corner.
     pub color2: Color,
+    /// Smooths the tile edges by averaging a 4x4 grid of samples per pixel.
+    /// The tiles then divide the image exactly, so edges may fall within a pixel instead of on a pixel boundary.
+    pub antialias: bool,
+    /// How far the tile edges are randomly moved, as a fraction of the tile size between 0 and 1.
+    /// Every edge moves as a whole line, so the tiles stay connected. A jitter of 0 keeps the regular pattern.
+    pub jitter: f32,
+    /// The seed that determines how the tile edges are moved.
+    pub seed: u64,
     pub properties: GeneratorProperties,
 }
 
+/// The amount of samples per pixel in each direction when a CheckerboardNode is antialiased.
+const CHECKERBOARD_SUBSAMPLES: u32 = 4;
+
 impl TextureTransformer<Rgb32FImage> for CheckerboardNode {
     fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
-        let width = self.properties.width;
-        let height = self.properties.height;
-        let section_width = width / (self.size_x + 1) as u32;
-        let section_height = height / (self.size_y + 1) as u32;
+        let (width, height) = self.properties.dimensions();
+        if self.antialias {
+            return self.generate_antialiased(width, height);
+        }
         ImageBuffer::from_fn(width, height, |x, y| {
-            if ((x / section_width)%2) == ((y / section_height)%2) {
+            let tile_x = self.pixel_tile(x, width, self.size_x + 1, 0);
+            let tile_y = self.pixel_tile(y, height, self.size_y + 1, 1);
+            if (tile_x%2) == (tile_y%2) {
                 self.color1
             } else {
                 self.color2
@@ -103,20 +308,111 @@ impl TextureTransformer<Rgb32FImage> for CheckerboardNode {
     fn inputs(&self) -> usize {
         0
     }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("Checkerboard({}x{} tiles, {}/{})", self.size_x + 1, self.size_y + 1, color_to_hex(&self.color1), color_to_hex(&self.color2))
+    }
+}
+
+impl_resizable!(CheckerboardNode);
+
+impl CheckerboardNode {
+    /// Creates a checkerboard with hard, regular tile edges. Antialiasing and jitter can be enabled on the returned node.
+    pub fn new(size_x: usize, size_y: usize, color1: Color, color2: Color, properties: GeneratorProperties) -> Self {
+        CheckerboardNode { size_x, size_y, color1, color2, antialias: false, jitter: 0.0, seed: 0, properties }
+    }
+
+    /// Returns the position of the edge before the given tile along an axis, including its jitter.
+    fn edge(&self, tile: i64, section: f32, axis: i64) -> f32 {
+        let offset = if self.jitter == 0.0 {
+            0.0
+        } else {
+            let random = (lattice_hash(self.seed, tile, axis) >> 40) as f32 / (1u64 << 24) as f32 - 0.5;
+            random * self.jitter.clamp(0.0, 1.0) * section
+        };
+        tile as f32 * section + offset
+    }
+
+    /// Finds the tile a position falls in along an axis, taking the jittered edges into account.
+    fn tile(&self, position: f32, section: f32, axis: i64) -> i64 {
+        let mut tile = (position / section) as i64;
+        while tile > 0 && self.edge(tile, section, axis) > position {
+            tile -= 1;
+        }
+        while self.edge(tile + 1, section, axis) <= position {
+            tile += 1;
+        }
+        tile
+    }
+
+    /// Finds the tile a pixel falls in along an axis with the given size and amount of tiles.
+    /// Without jitter, the pixels are distributed over the tiles as evenly as possible, so tile widths differ by at most one pixel
+    /// and the last tile is never wider or narrower than the rest, which keeps the pattern seamless.
+    fn pixel_tile(&self, position: u32, size: u32, tiles: usize, axis: i64) -> i64 {
+        if self.jitter == 0.0 {
+            (position as u64 * tiles as u64 / size as u64) as i64
+        } else {
+            self.tile(position as f32, (size as f32 / tiles as f32).max(1.0), axis)
+        }
+    }
+
+    fn generate_antialiased(&self, width: u32, height: u32) -> Rgb32FImage {
+        let section_width = (width as f32 / (self.size_x + 1) as f32).max(1.0);
+        let section_height = (height as f32 / (self.size_y + 1) as f32).max(1.0);
+        let samples = (CHECKERBOARD_SUBSAMPLES * CHECKERBOARD_SUBSAMPLES) as f32;
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let mut first = 0;
+            for i in 0..CHECKERBOARD_SUBSAMPLES {
+                for j in 0..CHECKERBOARD_SUBSAMPLES {
+                    let sx = x as f32 + (i as f32 + 0.5) / CHECKERBOARD_SUBSAMPLES as f32;
+                    let sy = y as f32 + (j as f32 + 0.5) / CHECKERBOARD_SUBSAMPLES as f32;
+                    let tile_x = self.tile(sx, section_width, 0);
+                    let tile_y = self.tile(sy, section_height, 1);
+                    if tile_x % 2 == tile_y % 2 {
+                        first += 1;
+                    }
+                }
+            }
+            let t = first as f32 / samples;
+            self.color1.map2(&self.color2, |c1, c2| c1 * t + c2 * (1.0 - t))
+        })
+    }
 }
 
 /// Generates a pattern of lines on a colored background
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LinesPosition {
     Start, Middle, End
 }
 
+/// The direction in which the lines of a LinesNode run
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineOrientation {
+    /// The lines run from left to right, and repeat in the vertical direction
+    #[default]
+    Horizontal,
+    /// The lines run from top to bottom, and repeat in the horizontal direction
+    Vertical
+}
+
+#[derive(Clone, Debug)]
 pub struct LinesNode {
-    /// The amount of lines on an image
+    /// The amount of lines on an image. A scale of 0 is treated as a single line.
     pub scale: usize,
     /// The proportion of line/background
     pub thickness: f32, 
     /// The position of the line on the background
     pub position: LinesPosition,
+    /// The direction in which the lines run
+    pub orientation: LineOrientation,
     /// The color of the background
     pub color1: Color,
     /// The color of the line 
@@ -126,12 +422,19 @@ pub struct LinesNode {
 
 impl TextureTransformer<Rgb32FImage> for LinesNode {
     fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
-        let width = self.properties.width;
-        let height = self.properties.height;
-        let section_height = height / self.scale as u32;
-        
-        Rgb32FImage::from_fn(width, height, |_x, y| {
-            let d_y = (y % section_height) as f32 / section_height as f32;
+        let (width, height) = self.properties.dimensions();
+        let length = match self.orientation {
+            LineOrientation::Horizontal => height,
+            LineOrientation::Vertical => width,
+        };
+        let section_height = (length / self.scale.max(1) as u32).max(1);
+
+        Rgb32FImage::from_fn(width, height, |x, y| {
+            let position = match self.orientation {
+                LineOrientation::Horizontal => y,
+                LineOrientation::Vertical => x,
+            };
+            let d_y = (position % section_height) as f32 / section_height as f32;
             match self.position {
                 LinesPosition::Start => if d_y <= self.thickness {self.color2} else {self.color1},
                 LinesPosition::Middle => if (0.5-d_y).abs() <= self.thickness / 2.0 {self.color2} else {self.color1},
@@ -143,16 +446,288 @@ impl TextureTransformer<Rgb32FImage> for LinesNode {
     fn inputs(&self) -> usize {
         0
     }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("Lines({} {:?} lines at {:?}, thickness {}, {}/{})", self.scale, self.orientation, self.position, self.thickness, color_to_hex(&self.color1), color_to_hex(&self.color2))
+    }
+}
+
+impl_resizable!(LinesNode);
+
+/// A node that loads an image from disk, resized to the dimensions in the properties.
+/// If the image can not be loaded, a solid magenta image is generated instead, so missing textures stand out.
+#[derive(Clone, Debug)]
+pub struct ImageFileNode {
+    pub path: PathBuf,
+    pub properties: GeneratorProperties,
+}
+
+impl ImageFileNode {
+    /// The color used when the image file can not be loaded.
+    pub const MISSING_COLOR: Color = Rgb([1.0, 0.0, 1.0]);
+}
+
+impl TextureTransformer<Rgb32FImage> for ImageFileNode {
+    fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        match image::open(&self.path) {
+            Ok(img) => imageops::resize(&img.to_rgb32f(), width, height, FilterType::Nearest),
+            Err(_) => ImageBuffer::from_pixel(width, height, Self::MISSING_COLOR),
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("ImageFile({})", self.path.display())
+    }
+}
+
+impl_resizable!(ImageFileNode);
+
+/// A node that produces alternating concentric rings around a center point, like a target.
+#[derive(Clone, Debug)]
+pub struct RingsNode {
+    /// The amount of rings between the center and a distance of the image width from it. A count of 0 is treated as 1.
+    pub count: u32,
+    /// The center of the rings, as a fraction of the width and height of the image.
+    pub center: (f32, f32),
+    /// The color of the innermost ring, and every other ring after it.
+    pub color1: Color,
+    /// The color of the remaining rings.
+    pub color2: Color,
+    pub properties: GeneratorProperties,
+}
+
+impl TextureTransformer<Rgb32FImage> for RingsNode {
+    fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        let count = self.count.max(1) as f32;
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let u = (x as f32 + 0.5) / width as f32 - self.center.0;
+            let v = ((y as f32 + 0.5) / height as f32 - self.center.1) * height as f32 / width as f32;
+            let radius = (u * u + v * v).sqrt();
+            if ((radius * count) as u32).is_multiple_of(2) {
+                self.color1
+            } else {
+                self.color2
+            }
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("Rings({} rings around {:?}, {}/{})", self.count, self.center, color_to_hex(&self.color1), color_to_hex(&self.color2))
+    }
+}
+
+impl_resizable!(RingsNode);
+
+/// A node that tiles the image with pointy-top hexagons.
+/// A hexagonal tiling needs three colors for neighbouring cells to always differ,
+/// so every third cell uses the average of both colors.
+#[derive(Clone, Debug)]
+pub struct HexagonNode {
+    /// The amount of hexagons along the width of the image. Larger sizes give smaller cells.
+    pub size: f32,
+    pub color1: Color,
+    pub color2: Color,
+    pub properties: GeneratorProperties,
+}
+
+impl HexagonNode {
+    /// Returns the distance from the center of a hexagon to its corners, in pixels.
+    fn radius(&self, width: u32) -> f32 {
+        width as f32 / (self.size * 3.0_f32.sqrt())
+    }
+
+    /// Returns the axial coordinates of the hexagon containing a position in pixels.
+    fn cell_at(&self, width: u32, x: f32, y: f32) -> (i64, i64) {
+        let radius = self.radius(width);
+        let q = (3.0_f32.sqrt() / 3.0 * x - y / 3.0) / radius;
+        let r = (2.0 / 3.0 * y) / radius;
+        // Round the cube coordinates, fixing the component with the largest rounding error so they still sum to zero.
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        (rq as i64, rr as i64)
+    }
+}
+
+impl TextureTransformer<Rgb32FImage> for HexagonNode {
+    fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        let average = self.color1.map2(&self.color2, |a, b| (a + b) / 2.0);
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let (q, r) = self.cell_at(width, x as f32 + 0.5, y as f32 + 0.5);
+            match (q - r).rem_euclid(3) {
+                0 => self.color1,
+                1 => self.color2,
+                _ => average,
+            }
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn is_valid(&self, _inputs: &Vec<&Rgb32FImage>) -> bool {
+        self.size > 0.0
+    }
+
+    fn describe(&self) -> String {
+        format!("Hexagon(size {}, {}/{})", self.size, color_to_hex(&self.color1), color_to_hex(&self.color2))
+    }
 }
 
+impl_resizable!(HexagonNode);
+
+/// Generates two sets of parallel lines crossing at a right angle on a colored background, for a crosshatch pattern.
+#[derive(Clone, Debug)]
+pub struct CrossHatchNode {
+    /// The distance between neighbouring lines of a set, in pixels. A spacing of 0 is treated as 1.
+    pub spacing: u32,
+    /// The proportion of line/background within the spacing. A thickness of 0 draws no lines.
+    pub thickness: f32,
+    /// The angle of the first set of lines in radians, where 0 gives vertical and horizontal lines.
+    /// The second set of lines is rotated by another quarter turn.
+    pub angle: f32,
+    /// The color of the background
+    pub color1: Color,
+    /// The color of the lines
+    pub color2: Color,
+    pub properties: GeneratorProperties,
+}
+
+impl TextureTransformer<Rgb32FImage> for CrossHatchNode {
+    fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        let spacing = self.spacing.max(1) as f32;
+        let (sin, cos) = self.angle.sin_cos();
+        let on_line = |position: f32| {
+            let fraction = (position / spacing).rem_euclid(1.0);
+            fraction.min(1.0 - fraction) < self.thickness / 2.0
+        };
+        Rgb32FImage::from_fn(width, height, |x, y| {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let u = px * cos + py * sin;
+            let v = py * cos - px * sin;
+            if on_line(u) || on_line(v) { self.color2 } else { self.color1 }
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("CrossHatch(spacing {}, thickness {}, angle {}, {}/{})", self.spacing, self.thickness, self.angle, color_to_hex(&self.color1), color_to_hex(&self.color2))
+    }
+}
+
+impl_resizable!(CrossHatchNode);
+
 #[cfg(test)]
 pub mod tests {
     use core::Gradient;
+    use std::path::PathBuf;
+
+    use graph::{Node, TextureGraph, TextureTransformer};
+    use image::{Rgb, Rgb32FImage};
 
-    use graph::TextureTransformer;
-    use image::Rgb;
+    use crate::{GradientFromInputsNode, CrossHatchNode, nodes::generators::{SolidColorNode, GeneratorProperties}, CheckerboardNode, LinesPosition, LinesNode, LineOrientation, GradientNodeDirection, GradientRepeat, GradientNode, ImageFileNode, RingsNode, HexagonNode, ConstantNode, BlendNode, BlendOptions};
 
-    use crate::{nodes::generators::{SolidColorNode, GeneratorProperties}, CheckerboardNode, LinesPosition, LinesNode, GradientNodeDirection, GradientNode};
+    #[test]
+    fn test_properties_zero_rejected() {
+        assert!(GeneratorProperties::new(0, 64).is_err());
+        assert!(GeneratorProperties::new(64, 0).is_err());
+        let properties = GeneratorProperties::new(64, 32).unwrap();
+        assert_eq!((64, 32), properties.dimensions());
+    }
+
+    #[test]
+    fn test_graph_resolution() {
+        let mut graph = TextureGraph::new();
+        let solid = graph.add_node(Node::new(String::from("Solid"), Box::new(SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::default()})));
+        let rings = graph.add_node(Node::new(String::from("Rings"), Box::new(RingsNode{
+            count: 4, center: (0.5, 0.5), color1: Rgb([0.0, 0.0, 0.0]), color2: Rgb([1.0, 1.0, 1.0]), properties: GeneratorProperties::default()})));
+        let blend = graph.add_node(Node::new(String::from("Blend"), Box::new(BlendNode::new(BlendOptions::Multiply))));
+        graph.add_edge(solid, blend, 0).unwrap();
+        graph.add_edge(rings, blend, 1).unwrap();
+        graph.generate_graph().unwrap();
+        graph.set_resolution(64, 32).unwrap();
+        assert!(graph.get_generated_node(&blend).is_none());
+        graph.generate_graph().unwrap();
+        for index in [solid, rings, blend] {
+            assert_eq!((64, 32), graph.get_generated_node(&index).unwrap().dimensions());
+        }
+    }
+
+    #[test]
+    fn test_properties_presets() {
+        assert_eq!((48, 48), GeneratorProperties::square(48).unwrap().dimensions());
+        assert!(GeneratorProperties::square(0).is_err());
+        assert_eq!((128, 128), GeneratorProperties::default().dimensions());
+        assert_eq!((256, 256), GeneratorProperties::p256().dimensions());
+        assert_eq!((512, 512), GeneratorProperties::p512().dimensions());
+        assert_eq!((1024, 1024), GeneratorProperties::p1024().dimensions());
+        let node = CheckerboardNode::new(1, 1, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::p256());
+        assert_eq!((256, 256), node.generate(Vec::new()).dimensions());
+        let node = SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::square(48).unwrap()};
+        assert_eq!((48, 48), node.generate(Vec::new()).dimensions());
+    }
+
+    #[test]
+    fn test_properties_minimum_size() {
+        let color = Rgb([1.0, 0.0, 0.0]);
+        let node = SolidColorNode{color, properties: GeneratorProperties { width: 0, height: 0 }};
+        let image = node.generate(Vec::new());
+        assert_eq!((1, 1), image.dimensions());
+        let node = GradientNode::new(Gradient{start: color, end: color}, GradientNodeDirection::RADIAL, GeneratorProperties { width: 0, height: 16 });
+        let image = node.generate(Vec::new());
+        assert_eq!((1, 16), image.dimensions());
+    }
 
     #[test]
     fn test_solid() {
@@ -175,6 +750,14 @@ pub mod tests {
         assert_eq!(64, image.height());
     }
     
+    #[test]
+    fn test_constant() {
+        let node = ConstantNode{value: 0.3, properties: GeneratorProperties { width: 32, height: 16 }};
+        let image = node.generate(Vec::new());
+        assert_eq!((32, 16), image.dimensions());
+        assert!(image.pixels().all(|&pix| pix == Rgb([0.3, 0.3, 0.3])));
+    }
+
     #[test]
     fn test_gradient_solid() {
         let color = Rgb([1.0, 0.0, 0.0]);
@@ -182,7 +765,7 @@ pub mod tests {
             start: color,
             end: color
         };
-        let node = GradientNode{gradient: gradient, direction: GradientNodeDirection::HORIZONTAL, properties: GeneratorProperties::default() };
+        let node = GradientNode::new(gradient, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
         let image = node.generate(Vec::new());
         assert!(image.pixels().all(|&pix| pix == color));
     }
@@ -193,7 +776,7 @@ pub mod tests {
             start: Rgb([0.0, 0.0, 0.0]),
             end: Rgb([1.0, 0.0, 0.0])
         };
-        let node = GradientNode{gradient: gradient, direction: GradientNodeDirection::HORIZONTAL, properties: GeneratorProperties::default() };
+        let node = GradientNode::new(gradient, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
         let image = node.generate(Vec::new());
         for y in 0..image.height() {
             for x in 0..image.width()-1 {
@@ -214,7 +797,7 @@ pub mod tests {
             start: Rgb([0.0, 0.0, 0.0]),
             end: Rgb([1.0, 0.0, 0.0])
         };
-        let node = GradientNode{gradient: gradient, direction: GradientNodeDirection::VERTICAL, properties: GeneratorProperties::default() };
+        let node = GradientNode::new(gradient, GradientNodeDirection::VERTICAL, GeneratorProperties::default());
         let image = node.generate(Vec::new());
         for y in 0..image.height() {
             let head = image.get_pixel(0, y);
@@ -235,7 +818,7 @@ pub mod tests {
             start: Rgb([1.0, 0.0, 0.0]),
             end: Rgb([0.0, 0.0, 0.0])
         };
-        let node = GradientNode{gradient: gradient, direction: GradientNodeDirection::RADIAL, properties: GeneratorProperties::default() };
+        let node = GradientNode::new(gradient, GradientNodeDirection::RADIAL, GeneratorProperties::default());
         let image = node.generate(Vec::new());
         for y in 0..image.height() {
             for x in 0..image.width()/2-1 {
@@ -257,9 +840,7 @@ pub mod tests {
             start: Rgb([1.0, 0.0, 0.0]),
             end: Rgb([0.0, 0.0, 0.0])
         };
-        let node = GradientNode{gradient: gradient, 
-            direction: GradientNodeDirection::RADIAL, 
-            properties: GeneratorProperties { width: 128, height: 128 } };
+        let node = GradientNode::new(gradient, GradientNodeDirection::RADIAL, GeneratorProperties { width: 128, height: 128 });
         let image = node.generate(Vec::new());
         assert_eq!(128, image.width());
         assert_eq!(128, image.height());
@@ -267,23 +848,41 @@ pub mod tests {
             start: Rgb([1.0, 0.0, 0.0]),
             end: Rgb([0.0, 0.0, 0.0])
         };
-        let node = GradientNode{gradient: gradient, 
-            direction: GradientNodeDirection::RADIAL, 
-            properties: GeneratorProperties { width: 64, height: 64 } };
+        let node = GradientNode::new(gradient, GradientNodeDirection::RADIAL, GeneratorProperties { width: 64, height: 64 });
         let image = node.generate(Vec::new());
         assert_eq!(64, image.width());
         assert_eq!(64, image.height());
     }
     
+    #[test]
+    fn test_gradient_repeat() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+        assert!(close(1.0, GradientRepeat::Clamp.apply(1.3)));
+        assert!(close(1.0, GradientRepeat::Clamp.apply(2.7)));
+        assert!(close(0.0, GradientRepeat::Clamp.apply(-0.5)));
+        assert!(close(0.3, GradientRepeat::Repeat.apply(1.3)));
+        assert!(close(0.7, GradientRepeat::Repeat.apply(2.7)));
+        assert!(close(0.7, GradientRepeat::Reflect.apply(1.3)));
+        assert!(close(0.7, GradientRepeat::Reflect.apply(2.7)));
+        assert!(close(0.4, GradientRepeat::Reflect.apply(0.4)));
+    }
+
+    #[test]
+    fn test_gradient_repeats() {
+        let gradient = Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) };
+        let mut node = GradientNode { repeat: GradientRepeat::Repeat, ..GradientNode::new(gradient, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default()) };
+        let image = node.generate(Vec::new());
+        assert!(image.enumerate_pixels().all(|(x, _, pix)| (pix.0[0] - x as f32 / 128.0).abs() < 1e-6));
+        node.repeats = 2.0;
+        let image = node.generate(Vec::new());
+        // Two full ramps, each starting at the start color.
+        assert!(image.enumerate_pixels().all(|(x, _, pix)| (pix.0[0] - (x % 64) as f32 / 64.0).abs() < 1e-5));
+        assert_eq!(node.gradient.start, *image.get_pixel(64, 0));
+    }
+
     #[test]
     fn test_checkerboard_default() {
-        let node = CheckerboardNode{
-            size_x: 1,
-            size_y: 1,
-            color1: Rgb([0.0, 0.0, 0.0]),
-            color2: Rgb([1.0, 1.0, 1.0]), 
-            properties: GeneratorProperties::default(),
-        };
+        let node = CheckerboardNode::new(1, 1, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::default());
         let image = node.generate(Vec::new());
         for x in 0..image.width() {
             for y in 0..image.height() {
@@ -298,13 +897,7 @@ pub mod tests {
 
     #[test]
     fn test_checkerboard_higher_scale() {
-        let node = CheckerboardNode{
-            size_x: 3,
-            size_y: 3,
-            color1: Rgb([0.0, 0.0, 0.0]),
-            color2: Rgb([1.0, 1.0, 1.0]),
-            properties: GeneratorProperties::default(),
-        };
+        let node = CheckerboardNode::new(3, 3, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::default());
         let image = node.generate(Vec::new());
         for x in 0..image.width() {
             for y in 0..image.height() {
@@ -317,15 +910,27 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_checkerboard_uneven_width() {
+        let node = CheckerboardNode::new(3, 0, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties { width: 130, height: 4 });
+        let image = node.generate(Vec::new());
+        let mut widths = vec![1];
+        for x in 1..image.width() {
+            if image.get_pixel(x, 0) == image.get_pixel(x - 1, 0) {
+                *widths.last_mut().unwrap() += 1;
+            } else {
+                widths.push(1);
+            }
+        }
+        assert_eq!(4, widths.len());
+        assert!(widths.iter().all(|&width| width == 32 || width == 33));
+        // The rightmost pixel belongs to the fourth tile, which starts with the second color.
+        assert_eq!(node.color2, *image.get_pixel(129, 0));
+    }
+
     #[test]
     fn test_checkerboard_separate_scales() {
-        let node = CheckerboardNode{
-            size_x: 1,
-            size_y: 3,
-            color1: Rgb([0.0, 0.0, 0.0]),
-            color2: Rgb([1.0, 1.0, 1.0]),
-            properties: GeneratorProperties::default(),
-        };
+        let node = CheckerboardNode::new(1, 3, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::default());
         let image = node.generate(Vec::new());
         for x in 0..image.width() {
             for y in 0..image.height() {
@@ -338,25 +943,58 @@ pub mod tests {
         }
     }
         
+    #[test]
+    fn test_checkerboard_more_tiles_than_pixels() {
+        let node = CheckerboardNode::new(500, 1, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::default());
+        let image = node.generate(Vec::new());
+        assert_eq!(128, image.width());
+        assert_eq!(image.get_pixel(0, 0).0, node.color1.0);
+        assert_eq!(image.get_pixel(1, 0).0, node.color2.0);
+    }
+
+    #[test]
+    fn test_checkerboard_antialias() {
+        let mut node = CheckerboardNode { antialias: true, ..CheckerboardNode::new(2, 0, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties { width: 128, height: 8 }) };
+        let image = node.generate(Vec::new());
+        // The first tile edge lies at x = 42.67, so three of the four sample columns of pixel 42 fall in the first tile.
+        let edge = image.get_pixel(42, 0).0[0];
+        assert!(edge > 0.0 && edge < 1.0);
+        assert!((edge - 0.25).abs() < 1e-6);
+        assert_eq!(node.color1, *image.get_pixel(41, 0));
+        assert_eq!(node.color2, *image.get_pixel(43, 0));
+        node.antialias = false;
+        let image = node.generate(Vec::new());
+        assert!(image.pixels().all(|pix| *pix == node.color1 || *pix == node.color2));
+    }
+
+    #[test]
+    fn test_checkerboard_jitter() {
+        let mut node = CheckerboardNode { seed: 7, ..CheckerboardNode::new(3, 3, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties::default()) };
+        let regular = node.generate(Vec::new());
+        for x in 0..regular.width() {
+            for y in 0..regular.height() {
+                let expected = if (x / 32) % 2 == (y / 32) % 2 { node.color1 } else { node.color2 };
+                assert_eq!(expected, *regular.get_pixel(x, y));
+            }
+        }
+        node.jitter = 0.5;
+        let jittered = node.generate(Vec::new());
+        assert_ne!(regular, jittered);
+        assert_eq!(jittered, node.generate(Vec::new()));
+        // The edges move as whole lines, so every column changes color at the same rows.
+        let changes = |x: u32| (1..jittered.height()).filter(|&y| jittered.get_pixel(x, y) != jittered.get_pixel(x, y - 1)).collect::<Vec<_>>();
+        assert!((1..jittered.width()).all(|x| changes(x) == changes(0)));
+        node.seed = 8;
+        assert_ne!(jittered, node.generate(Vec::new()));
+    }
+
     #[test]
     fn test_checkerboard_properties() {
-        let node = CheckerboardNode{
-            size_x: 1,
-            size_y: 3,
-            color1: Rgb([0.0, 0.0, 0.0]),
-            color2: Rgb([1.0, 1.0, 1.0]),
-            properties: GeneratorProperties { width: 128, height: 128 }
-        };
+        let node = CheckerboardNode::new(1, 3, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties { width: 128, height: 128 });
         let image = node.generate(Vec::new());
         assert_eq!(128, image.width());
         assert_eq!(128, image.height());
-        let node = CheckerboardNode{
-            size_x: 1,
-            size_y: 3,
-            color1: Rgb([0.0, 0.0, 0.0]),
-            color2: Rgb([1.0, 1.0, 1.0]),
-            properties: GeneratorProperties { width: 64, height: 64 }
-        };
+        let node = CheckerboardNode::new(1, 3, Rgb([0.0, 0.0, 0.0]), Rgb([1.0, 1.0, 1.0]), GeneratorProperties { width: 64, height: 64 });
         let image = node.generate(Vec::new());
         assert_eq!(64, image.width());
         assert_eq!(64, image.height());
@@ -366,7 +1004,7 @@ pub mod tests {
         let color1 = Rgb([0.0, 0.0, 0.0]);
         let color2 = Rgb([1.0, 1.0, 1.0]);
         let node = LinesNode {
-            scale, thickness, position, color1, color2, properties: GeneratorProperties::default()
+            scale, thickness, position, orientation: LineOrientation::Horizontal, color1, color2, properties: GeneratorProperties::default()
         };
         let image = node.generate(Vec::new());
         for y in 0..image.height() {
@@ -409,12 +1047,53 @@ pub mod tests {
         test_line_helper(2, 0.5, LinesPosition::End);
     }
             
+    #[test]
+    fn test_lines_vertical() {
+        let color1 = Rgb([0.0, 0.0, 0.0]);
+        let color2 = Rgb([1.0, 1.0, 1.0]);
+        let node = LinesNode {
+            scale: 4, thickness: 0.25, position: LinesPosition::Start, orientation: LineOrientation::Vertical, color1, color2,
+            properties: GeneratorProperties::default()
+        };
+        let image = node.generate(Vec::new());
+        for x in 0..image.width() {
+            let head = image.get_pixel(x, 0);
+            for y in 0..image.height() {
+                assert_eq!(head, image.get_pixel(x, y));
+            }
+        }
+        let section_size = image.width() / 4;
+        for x in 0..image.width() - section_size {
+            assert_eq!(image.get_pixel(x, 0), image.get_pixel(x + section_size, 0));
+        }
+        assert_eq!(&color2, image.get_pixel(0, 0));
+        assert_eq!(&color1, image.get_pixel(section_size - 1, 0));
+    }
+
+    #[test]
+    fn test_lines_zero_scale() {
+        let color1 = Rgb([0.0, 0.0, 0.0]);
+        let color2 = Rgb([1.0, 1.0, 1.0]);
+        let node = LinesNode {
+            scale: 0, thickness: 0.5, position: LinesPosition::Start, orientation: LineOrientation::Horizontal, color1, color2,
+            properties: GeneratorProperties::default()
+        };
+        let image = node.generate(Vec::new());
+        let single = LinesNode {
+            scale: 1, thickness: 0.5, position: LinesPosition::Start, orientation: LineOrientation::Horizontal, color1, color2,
+            properties: GeneratorProperties::default()
+        }.generate(Vec::new());
+        assert_eq!(single, image);
+        assert_eq!(&color2, image.get_pixel(0, 0));
+        assert_eq!(&color1, image.get_pixel(0, image.height() - 1));
+    }
+
     #[test]
     fn test_lines_properties() {
         let color1 = Rgb([0.0, 0.0, 0.0]);
         let color2 = Rgb([1.0, 1.0, 1.0]);
         let node = LinesNode {
-            scale: 2, thickness: 0.5, position: LinesPosition::Start, color1, color2, 
+            scale: 2, thickness: 0.5, position: LinesPosition::Start, orientation: LineOrientation::Horizontal, color1, color2, 
             properties: GeneratorProperties { width: 128, height: 128 }
         };
         let image = node.generate(Vec::new());
@@ -423,11 +1102,148 @@ pub mod tests {
         let color1 = Rgb([0.0, 0.0, 0.0]);
         let color2 = Rgb([1.0, 1.0, 1.0]);
         let node = LinesNode {
-            scale: 2, thickness: 0.5, position: LinesPosition::Start, color1, color2, 
+            scale: 2, thickness: 0.5, position: LinesPosition::Start, orientation: LineOrientation::Horizontal, color1, color2, 
             properties: GeneratorProperties { width: 64, height: 64 }
         };
         let image = node.generate(Vec::new());
         assert_eq!(64, image.width());
         assert_eq!(64, image.height());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_image_file() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quadrants.png");
+        let node = ImageFileNode{path, properties: GeneratorProperties { width: 8, height: 8 }};
+        let image = node.generate(Vec::new());
+        assert_eq!(8, image.width());
+        assert_eq!(8, image.height());
+        assert_eq!(&Rgb([1.0, 0.0, 0.0]), image.get_pixel(0, 0));
+        assert_eq!(&Rgb([0.0, 1.0, 0.0]), image.get_pixel(7, 0));
+        assert_eq!(&Rgb([0.0, 0.0, 1.0]), image.get_pixel(0, 7));
+        assert_eq!(&Rgb([1.0, 1.0, 1.0]), image.get_pixel(7, 7));
+    }
+
+    #[test]
+    fn test_image_file_missing() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/missing.png");
+        let node = ImageFileNode{path, properties: GeneratorProperties::default()};
+        let image = node.generate(Vec::new());
+        assert_eq!(128, image.width());
+        assert!(image.pixels().all(|&pix| pix == ImageFileNode::MISSING_COLOR));
+    }
+
+    #[test]
+    fn test_rings() {
+        let color1 = Rgb([1.0, 0.0, 0.0]);
+        let color2 = Rgb([0.0, 0.0, 1.0]);
+        let node = RingsNode{count: 4, center: (0.5, 0.5), color1, color2, properties: GeneratorProperties::default()};
+        let image = node.generate(Vec::new());
+        assert_eq!(&color1, image.get_pixel(64, 64));
+        // Each ring is a quarter of the width wide, so the boundaries right of the center are at 96 and 128 pixels.
+        assert_eq!(&color1, image.get_pixel(95, 64));
+        assert_eq!(&color2, image.get_pixel(96, 64));
+        assert_eq!(&color2, image.get_pixel(64, 127));
+        assert_eq!(&color1, image.get_pixel(64, 32));
+        assert_eq!(&color2, image.get_pixel(64, 31));
+    }
+
+    #[test]
+    fn test_rings_off_center() {
+        let color1 = Rgb([1.0, 0.0, 0.0]);
+        let color2 = Rgb([0.0, 0.0, 1.0]);
+        let node = RingsNode{count: 2, center: (0.0, 0.0), color1, color2, properties: GeneratorProperties { width: 64, height: 32 }};
+        let image = node.generate(Vec::new());
+        assert_eq!(&color1, image.get_pixel(0, 0));
+        assert_eq!(&color1, image.get_pixel(31, 0));
+        assert_eq!(&color2, image.get_pixel(32, 0));
+        // Distances are measured relative to the width, so the bottom edge is only half a ring away.
+        assert_eq!(&color1, image.get_pixel(0, 31));
+        assert_eq!(&color2, image.get_pixel(40, 20));
+    }
+
+    fn test_hexagon_node(size: f32) -> HexagonNode {
+        HexagonNode{size, color1: Rgb([1.0, 0.0, 0.0]), color2: Rgb([0.0, 0.0, 1.0]), properties: GeneratorProperties::default()}
+    }
+
+    #[test]
+    fn test_hexagon_neighbours_differ() {
+        let node = test_hexagon_node(6.0);
+        let image = node.generate(Vec::new());
+        let radius = node.radius(image.width());
+        let center = |q: i64, r: i64| {
+            let x = radius * 3.0_f32.sqrt() * (q as f32 + r as f32 / 2.0);
+            let y = radius * 1.5 * r as f32;
+            *image.get_pixel(x as u32, y as u32)
+        };
+        let color = center(2, 2);
+        for (dq, dr) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, -1), (-1, 1)] {
+            assert_ne!(color, center(2 + dq, 2 + dr));
+        }
+    }
+
+    #[test]
+    fn test_hexagon_size() {
+        let transitions = |size: f32| {
+            let image = test_hexagon_node(size).generate(Vec::new());
+            (1..image.width()).filter(|&x| image.get_pixel(x, 0) != image.get_pixel(x - 1, 0)).count() as i64
+        };
+        let small = transitions(4.0);
+        let large = transitions(8.0);
+        assert!((small - 4).abs() <= 1);
+        assert!((large - 2 * small).abs() <= 2);
+    }
+
+    #[test]
+    fn test_cross_hatch() {
+        let background = Rgb([0.0, 0.0, 0.0]);
+        let line = Rgb([1.0, 1.0, 1.0]);
+        let node = CrossHatchNode{spacing: 16, thickness: 0.25, angle: 0.0, color1: background, color2: line, properties: GeneratorProperties::default()};
+        let image = node.generate(vec![]);
+        // Lines of both sets run through the pixels at multiples of the spacing, and overlap where they cross.
+        assert_eq!(line, *image.get_pixel(16, 16));
+        assert_eq!(line, *image.get_pixel(16, 8));
+        assert_eq!(line, *image.get_pixel(8, 16));
+        assert_eq!(background, *image.get_pixel(8, 8));
+        let lines = image.pixels().filter(|pix| **pix == line).count();
+        // Each set covers a quarter of the columns or rows, and the crossings are shared by both sets.
+        let columns = 128 / 4;
+        assert_eq!(2 * columns * 128 - columns * columns, lines);
+        // A quarter turn swaps the two sets, which gives the same pattern.
+        let turned = CrossHatchNode{angle: std::f32::consts::FRAC_PI_2, ..node.clone()}.generate(vec![]);
+        assert_eq!(lines, turned.pixels().filter(|pix| **pix == line).count());
+        let solid = CrossHatchNode{thickness: 0.0, angle: 0.4, ..node}.generate(vec![]);
+        assert!(solid.pixels().all(|pix| *pix == background));
+    }
+
+    #[test]
+    fn test_gradient_from_inputs() {
+        let start = Rgb([0.2, 0.0, 0.4]);
+        let end = Rgb([1.0, 0.6, 0.0]);
+        let properties = GeneratorProperties { width: 64, height: 32 };
+        let mut graph = TextureGraph::<Rgb32FImage>::new();
+        let start_node = graph.add_node(Node::new(String::from("Start"), Box::new(SolidColorNode{color: start, properties: GeneratorProperties::default()})));
+        let end_node = graph.add_node(Node::new(String::from("End"), Box::new(SolidColorNode{color: end, properties: GeneratorProperties::default()})));
+        let gradient = graph.add_node(Node::new(String::from("Gradient"), Box::new(GradientFromInputsNode{
+            direction: GradientNodeDirection::RADIAL,
+            repeat: GradientRepeat::Reflect,
+            repeats: 2.0,
+            properties: properties.clone() })));
+        graph.add_edge(start_node, gradient, 0).unwrap();
+        graph.add_edge(end_node, gradient, 1).unwrap();
+        graph.generate_graph().unwrap();
+        let expected = GradientNode { repeat: GradientRepeat::Reflect, repeats: 2.0, ..GradientNode::new(Gradient { start, end }, GradientNodeDirection::RADIAL, properties) };
+        assert_eq!(Some(&expected.generate(vec![])), graph.get_generated_node(&gradient));
+        assert_eq!(vec!["start", "end"], graph.input_names(gradient).unwrap());
+    }
+
+    #[test]
+    fn test_gradient_describe_repeat() {
+        let gradient = Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) };
+        let node = GradientNode::new(gradient, GradientNodeDirection::VERTICAL, GeneratorProperties::default());
+        assert_eq!("Gradient(vertical, clamp x1, #000000→#ffffff)", node.describe());
+        let node = GradientNode { repeat: GradientRepeat::Reflect, repeats: 2.5, ..node };
+        assert_eq!("Gradient(vertical, reflect x2.5, #000000→#ffffff)", node.describe());
+        let node = GradientFromInputsNode { direction: GradientNodeDirection::RADIAL, repeat: GradientRepeat::Repeat, repeats: 3.0, properties: GeneratorProperties::default() };
+        assert_eq!("GradientFromInputs(radial, repeat x3)", node.describe());
+    }
+}
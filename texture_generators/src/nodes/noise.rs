@@ -0,0 +1,436 @@
+use core::{Color, Gradient};
+use std::f32::consts::{SQRT_2, TAU};
+
+use graph::{hash_debug, TextureTransformer};
+use image::Rgb32FImage;
+
+use crate::{EdgeMode, GeneratorProperties, sample};
+
+/// Hashes a lattice point of the noise together with the seed, so every point gets a pseudo-random gradient.
+pub(crate) fn lattice_hash(seed: u64, x: i64, y: i64) -> u64 {
+    let mut h = seed ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    h ^= h >> 30;
+    h = h.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^= h >> 27;
+    h = h.wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+/// The quintic fade curve of improved Perlin noise, which makes the noise smooth across lattice cells.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// Evaluates 2D Perlin noise at a position in lattice cells, returning a value between 0 and 1.
+/// If a period is given, the lattice repeats after that many cells, so the noise tiles with that period.
+pub(crate) fn perlin(seed: u64, x: f32, y: f32, period: Option<i64>) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let corner = |cx: i64, cy: i64| {
+        let (lx, ly) = match period {
+            Some(p) => (cx.rem_euclid(p), cy.rem_euclid(p)),
+            None => (cx, cy),
+        };
+        let angle = (lattice_hash(seed, lx, ly) >> 40) as f32 / (1u64 << 24) as f32 * TAU;
+        let (gy, gx) = angle.sin_cos();
+        gx * (x - cx as f32) + gy * (y - cy as f32)
+    };
+    let (u, v) = (fade(fx), fade(fy));
+    let top = corner(x0, y0) + (corner(x0 + 1, y0) - corner(x0, y0)) * u;
+    let bottom = corner(x0, y0 + 1) + (corner(x0 + 1, y0 + 1) - corner(x0, y0 + 1)) * u;
+    let value = top + (bottom - top) * v;
+    ((value * SQRT_2 + 1.0) / 2.0).clamp(0.0, 1.0)
+}
+
+/// A node that generates Perlin noise, a smooth random pattern often used for clouds, dirt, and as input for other nodes.
+#[derive(Clone, Debug)]
+pub struct PerlinNode {
+    /// The amount of noise cells along the width of the image. A scale of 0 is treated as a single cell.
+    pub scale: u32,
+    /// Different seeds produce different noise.
+    pub seed: u64,
+    /// Makes the noise wrap around, so the left and right edges, and the top and bottom edges, continue into each other.
+    pub tileable: bool,
+    /// The colors the noise values 0 and 1 are mapped to.
+    pub gradient: Gradient,
+    pub properties: GeneratorProperties,
+}
+
+impl TextureTransformer<Rgb32FImage> for PerlinNode {
+    fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        let scale = self.scale.max(1);
+        let cell_size = width as f32 / scale as f32;
+        // Tileable noise repeats its lattice after one image, so the cells are stretched to fit a whole amount in the height as well.
+        let period = self.tileable.then_some(scale as i64);
+        let cell_height = if self.tileable { height as f32 / scale as f32 } else { cell_size };
+        Rgb32FImage::from_fn(width, height, |x, y| {
+            let value = perlin(self.seed, x as f32 / cell_size, y as f32 / cell_height, period);
+            self.gradient.get_color(value)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("Perlin(scale {}, seed {}, {})", self.scale, self.seed, self.gradient.describe())
+    }
+}
+
+impl_resizable!(PerlinNode);
+
+/// A node that generates fractal noise by summing octaves of Perlin noise, which adds fine detail to the large shapes.
+/// This is a generator rather than a transformer of a noise input, so each octave can be sampled at its own frequency.
+#[derive(Clone, Debug)]
+pub struct TurbulenceNode {
+    /// The amount of noise cells along the width of the image in the first octave. A scale of 0 is treated as a single cell.
+    pub scale: u32,
+    /// The amount of octaves that are summed. An octave count of 0 is treated as a single octave.
+    pub octaves: u32,
+    /// The factor by which the frequency grows for each octave, usually 2.
+    pub lacunarity: f32,
+    /// The factor by which the strength shrinks for each octave, usually 0.5.
+    pub gain: f32,
+    /// Different seeds produce different noise.
+    pub seed: u64,
+    /// The colors the noise values 0 and 1 are mapped to.
+    pub gradient: Gradient,
+    pub properties: GeneratorProperties,
+}
+
+impl TextureTransformer<Rgb32FImage> for TurbulenceNode {
+    fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        let cell_size = width as f32 / self.scale.max(1) as f32;
+        let octaves: Vec<(u64, f32, f32)> = (0..self.octaves.max(1))
+            .map(|i| (self.seed.wrapping_add(i as u64), self.lacunarity.powi(i as i32), self.gain.powi(i as i32)))
+            .collect();
+        let total: f32 = octaves.iter().map(|(_, _, amplitude)| amplitude).sum();
+        Rgb32FImage::from_fn(width, height, |x, y| {
+            let (u, v) = (x as f32 / cell_size, y as f32 / cell_size);
+            let sum: f32 = octaves.iter()
+                .map(|&(seed, frequency, amplitude)| amplitude * perlin(seed, u * frequency, v * frequency, None))
+                .sum();
+            self.gradient.get_color((sum / total).clamp(0.0, 1.0))
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn is_valid(&self, _inputs: &Vec<&Rgb32FImage>) -> bool {
+        self.gain > 0.0 && self.lacunarity > 0.0
+    }
+
+    fn describe(&self) -> String {
+        format!("Turbulence(scale {}, {} octaves, seed {}, {})", self.scale, self.octaves, self.seed, self.gradient.describe())
+    }
+}
+
+impl_resizable!(TurbulenceNode);
+
+/// The way distances to the feature points of a VoronoiNode are measured, which changes the shape of the cells.
+#[derive(Clone, Copy, Debug)]
+pub enum DistanceMetric {
+    /// Straight-line distance, which gives cells with straight edges at any angle.
+    Euclidean,
+    /// Sum of the horizontal and vertical distance, which gives diamond-like cells.
+    Manhattan,
+    /// Largest of the horizontal and vertical distance, which gives boxy cells.
+    Chebyshev,
+}
+
+impl DistanceMetric {
+    fn distance(&self, dx: f32, dy: f32) -> f32 {
+        match self {
+            DistanceMetric::Euclidean => (dx * dx + dy * dy).sqrt(),
+            DistanceMetric::Manhattan => dx.abs() + dy.abs(),
+            DistanceMetric::Chebyshev => dx.abs().max(dy.abs()),
+        }
+    }
+}
+
+/// What a VoronoiNode draws.
+#[derive(Clone, Debug)]
+pub enum VoronoiOutput {
+    /// Shades each pixel by the distance to the nearest feature point, which gives smooth cells.
+    Distance,
+    /// Draws the edges between cells on top of the distance shading, which gives cracks or cellular walls.
+    /// A pixel is on an edge where the difference between the distance to the nearest and second nearest feature point
+    /// is less than the thickness, measured in cells.
+    Cracks { thickness: f32, color: Color },
+}
+
+/// Returns the feature point of a lattice cell, at a pseudo-random position inside the cell.
+fn feature_point(seed: u64, cx: i64, cy: i64) -> (f32, f32) {
+    let h = lattice_hash(seed, cx, cy);
+    let rx = (h >> 40) as f32 / (1u64 << 24) as f32;
+    let ry = ((h >> 16) & 0xFF_FFFF) as f32 / (1u64 << 24) as f32;
+    (cx as f32 + rx, cy as f32 + ry)
+}
+
+/// Returns the distances to the nearest and second nearest feature point from a position in lattice cells,
+/// where `feature_point` gives the feature point of every lattice cell.
+fn nearest_two(feature_point: impl Fn(i64, i64) -> (f32, f32), metric: DistanceMetric, x: f32, y: f32) -> (f32, f32) {
+    let (cx, cy) = (x.floor() as i64, y.floor() as i64);
+    let mut nearest = (f32::MAX, f32::MAX);
+    for ny in cy - 2..=cy + 2 {
+        for nx in cx - 2..=cx + 2 {
+            let (px, py) = feature_point(nx, ny);
+            let distance = metric.distance(px - x, py - y);
+            if distance < nearest.0 {
+                nearest = (distance, nearest.0);
+            } else if distance < nearest.1 {
+                nearest.1 = distance;
+            }
+        }
+    }
+    nearest
+}
+
+/// A node that generates Voronoi cells around randomly placed feature points, one in every cell of a grid.
+#[derive(Clone, Debug)]
+pub struct VoronoiNode {
+    /// The amount of cells along the width of the image. A scale of 0 is treated as a single cell.
+    pub scale: u32,
+    /// Different seeds produce different cells.
+    pub seed: u64,
+    pub metric: DistanceMetric,
+    pub output: VoronoiOutput,
+    /// The colors of the feature points and of pixels a whole cell away from the nearest feature point.
+    pub gradient: Gradient,
+    pub properties: GeneratorProperties,
+}
+
+impl VoronoiNode {
+    /// Returns the color at a position in lattice cells, given the feature point of every lattice cell.
+    fn color_at(&self, feature_point: impl Fn(i64, i64) -> (f32, f32), u: f32, v: f32) -> Color {
+        let (f1, f2) = nearest_two(feature_point, self.metric, u, v);
+        match &self.output {
+            VoronoiOutput::Cracks { thickness, color } if f2 - f1 < *thickness => *color,
+            _ => self.gradient.get_color(f1.clamp(0.0, 1.0)),
+        }
+    }
+}
+
+impl TextureTransformer<Rgb32FImage> for VoronoiNode {
+    fn generate(&self, _inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (width, height) = self.properties.dimensions();
+        let cell_size = width as f32 / self.scale.max(1) as f32;
+        Rgb32FImage::from_fn(width, height, |x, y| {
+            let (u, v) = ((x as f32 + 0.5) / cell_size, (y as f32 + 0.5) / cell_size);
+            self.color_at(|cx, cy| feature_point(self.seed, cx, cy), u, v)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    generator_size!();
+
+    fn describe(&self) -> String {
+        format!("Voronoi(scale {}, seed {}, {:?}, {})", self.scale, self.seed, self.metric, self.gradient.describe())
+    }
+}
+
+impl_resizable!(VoronoiNode);
+
+/// A node that distorts its input by moving every pixel along a Perlin noise field, which makes patterns look organic.
+/// The noise tiles with the image and the input wraps around at the edges, so tileable inputs stay tileable.
+#[derive(Clone, Debug)]
+pub struct NoiseWarpNode {
+    /// The amount of noise cells along the width and height of the image. A scale of 0 is treated as a single cell.
+    pub scale: u32,
+    /// The largest distance in pixels a pixel is moved. A strength of 0 leaves the input unchanged.
+    pub strength: f32,
+    /// Different seeds produce different distortions.
+    pub seed: u64,
+}
+
+impl TextureTransformer<Rgb32FImage> for NoiseWarpNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        let scale = self.scale.max(1);
+        let cell_width = image.width() as f32 / scale as f32;
+        let cell_height = image.height() as f32 / scale as f32;
+        let period = Some(scale as i64);
+        Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+            let (u, v) = (x as f32 / cell_width, y as f32 / cell_height);
+            let dx = (perlin(self.seed, u, v, period) * 2.0 - 1.0) * self.strength;
+            let dy = (perlin(self.seed.wrapping_add(1), u, v, period) * 2.0 - 1.0) * self.strength;
+            sample(image, x as f32 + dx, y as f32 + dy, EdgeMode::Wrap)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("NoiseWarp(scale {}, strength {}, seed {})", self.scale, self.strength, self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::Gradient;
+
+    use graph::TextureTransformer;
+    use image::{Rgb, Rgb32FImage};
+
+    use crate::{GeneratorProperties, PerlinNode, TurbulenceNode, VoronoiNode, DistanceMetric, VoronoiOutput, NoiseWarpNode};
+
+    fn perlin_node(tileable: bool) -> PerlinNode {
+        PerlinNode {
+            scale: 4, seed: 7, tileable,
+            gradient: Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) },
+            properties: GeneratorProperties::default()
+        }
+    }
+
+    /// Returns the largest difference between the first and last column, and between the first and last row.
+    fn edge_difference(image: &Rgb32FImage) -> (f32, f32) {
+        let (width, height) = image.dimensions();
+        let columns = (0..height)
+            .map(|y| (image.get_pixel(0, y).0[0] - image.get_pixel(width - 1, y).0[0]).abs())
+            .fold(0.0, f32::max);
+        let rows = (0..width)
+            .map(|x| (image.get_pixel(x, 0).0[0] - image.get_pixel(x, height - 1).0[0]).abs())
+            .fold(0.0, f32::max);
+        (columns, rows)
+    }
+
+    #[test]
+    fn test_perlin_range() {
+        let image = perlin_node(false).generate(vec![]);
+        assert!(image.pixels().all(|pix| (0.0..=1.0).contains(&pix.0[0])));
+        let min = image.pixels().map(|pix| pix.0[0]).fold(1.0, f32::min);
+        let max = image.pixels().map(|pix| pix.0[0]).fold(0.0, f32::max);
+        assert!(max - min > 0.3);
+    }
+
+    #[test]
+    fn test_perlin_seed() {
+        let image1 = perlin_node(false).generate(vec![]);
+        let image2 = PerlinNode { seed: 8, ..perlin_node(false) }.generate(vec![]);
+        assert_eq!(image1, perlin_node(false).generate(vec![]));
+        assert_ne!(image1, image2);
+    }
+
+    #[test]
+    fn test_perlin_tileable() {
+        let (columns, rows) = edge_difference(&perlin_node(true).generate(vec![]));
+        assert!(columns < 0.05 && rows < 0.05);
+        let (columns, rows) = edge_difference(&perlin_node(false).generate(vec![]));
+        assert!(columns > 0.05 && rows > 0.05);
+    }
+
+    /// Returns the mean squared difference between horizontally neighbouring pixels relative to the variance of the image,
+    /// a measure of how much of the variation is high-frequency detail.
+    fn high_frequency_ratio(image: &Rgb32FImage) -> f32 {
+        let (width, height) = image.dimensions();
+        let values: Vec<f32> = image.pixels().map(|pix| pix.0[0]).collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+        let mut sum = 0.0;
+        for y in 0..height {
+            for x in 1..width {
+                sum += (image.get_pixel(x, y).0[0] - image.get_pixel(x - 1, y).0[0]).powi(2);
+            }
+        }
+        sum / ((width - 1) * height) as f32 / variance
+    }
+
+    #[test]
+    fn test_turbulence_octaves() {
+        let node = |octaves| TurbulenceNode {
+            scale: 4, octaves, lacunarity: 2.0, gain: 0.5, seed: 3,
+            gradient: Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) },
+            properties: GeneratorProperties::default()
+        };
+        let single = node(1).generate(vec![]);
+        let fractal = node(5).generate(vec![]);
+        assert!(high_frequency_ratio(&fractal) > 2.0 * high_frequency_ratio(&single));
+        assert!(fractal.pixels().all(|pix| (0.0..=1.0).contains(&pix.0[0])));
+        // A single octave is plain Perlin noise.
+        let perlin = PerlinNode { seed: 3, ..perlin_node(false) }.generate(vec![]);
+        assert_eq!(perlin, single);
+    }
+
+    fn voronoi_node(metric: DistanceMetric, output: VoronoiOutput) -> VoronoiNode {
+        VoronoiNode {
+            scale: 4, seed: 11, metric, output,
+            gradient: Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) },
+            properties: GeneratorProperties::default()
+        }
+    }
+
+    #[test]
+    fn test_voronoi_cracks_equidistant() {
+        // With the feature points in the centers of the lattice cells, the edges between cells lie on the lattice lines.
+        let centers = |cx: i64, cy: i64| (cx as f32 + 0.5, cy as f32 + 0.5);
+        let crack_color = Rgb([1.0, 0.0, 0.0]);
+        for metric in [DistanceMetric::Euclidean, DistanceMetric::Manhattan, DistanceMetric::Chebyshev] {
+            let node = voronoi_node(metric, VoronoiOutput::Cracks { thickness: 0.05, color: crack_color });
+            // A distance d from the edge between two points makes their distances differ by 2d.
+            assert_eq!(crack_color, node.color_at(centers, 1.0, 0.5));
+            assert_eq!(crack_color, node.color_at(centers, 1.02, 0.5));
+            assert_eq!(crack_color, node.color_at(centers, 2.5, 2.98));
+            assert_eq!(crack_color, node.color_at(centers, 3.0, 3.0));
+            assert_ne!(crack_color, node.color_at(centers, 1.03, 0.5));
+            assert_ne!(crack_color, node.color_at(centers, 2.5, 2.96));
+            assert_eq!(Rgb([0.0, 0.0, 0.0]), node.color_at(centers, 0.5, 0.5));
+            let image = node.generate(vec![]);
+            let cracks = image.pixels().filter(|pix| **pix == crack_color).count();
+            assert!(cracks > 0 && cracks < image.pixels().len() / 4);
+        }
+    }
+
+    #[test]
+    fn test_voronoi_metric() {
+        let euclidean = voronoi_node(DistanceMetric::Euclidean, VoronoiOutput::Distance).generate(vec![]);
+        let manhattan = voronoi_node(DistanceMetric::Manhattan, VoronoiOutput::Distance).generate(vec![]);
+        let chebyshev = voronoi_node(DistanceMetric::Chebyshev, VoronoiOutput::Distance).generate(vec![]);
+        // Manhattan distances are never shorter, and Chebyshev distances are never longer than Euclidean distances.
+        assert!(euclidean.pixels().zip(manhattan.pixels()).all(|(e, m)| m.0[0] >= e.0[0] - 1e-6));
+        assert!(euclidean.pixels().zip(chebyshev.pixels()).all(|(e, c)| c.0[0] <= e.0[0] + 1e-6));
+        assert_ne!(euclidean, manhattan);
+    }
+
+    #[test]
+    fn test_noise_warp() {
+        let input = perlin_node(true).generate(vec![]);
+        let warp = |strength: f32, seed: u64| NoiseWarpNode { scale: 3, strength, seed }.generate(vec![&input]);
+        assert_eq!(input, warp(0.0, 1));
+        let warped = warp(6.0, 1);
+        assert_ne!(input, warped);
+        assert_eq!(warped, warp(6.0, 1));
+        assert_ne!(warped, warp(6.0, 2));
+    }
+}
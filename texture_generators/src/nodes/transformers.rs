@@ -1,59 +1,1212 @@
-use core::average_color;
+use core::{arithmetic, color_to_hex, Brightness, Color};
+use std::borrow::Cow;
 
-use graph::TextureTransformer;
-use image::{Rgb32FImage, Pixel, Rgb};
+use graph::{hash_debug, ChannelStats, TextureTransformer};
+use image::{Rgb32FImage, Pixel, Rgb, imageops};
 
+use crate::{mean_color, par_from_fn};
+
+/// Checks whether all images have the same dimensions, which transformers with several inputs usually require.
+pub fn all_same_size(inputs: &[&Rgb32FImage]) -> bool {
+    inputs.windows(2).all(|pair| pair[0].dimensions() == pair[1].dimensions())
+}
+
+/// The output size of transformers that require all inputs to have the same size, which is then also the output size.
+fn same_size_output(input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+    let first = *input_sizes.first()?;
+    input_sizes.iter().all(|&size| size == first).then_some(first)
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlendOptions {
     Add,
     Subtract,
     Multiply,
-    Mask(f32),
+    /// Uses the first input as the mask and the second input as the content.
+    /// Outputs the content where the brightness of the mask is at least the threshold, and black otherwise.
+    Mask(f32, Brightness),
+    /// Uses the second input as the mask and the first input as the content.
+    /// Outputs the content where the brightness of the mask is at least the threshold, and black otherwise.
+    MaskInverted(f32, Brightness),
+    /// Uses the first input as the base and the second input as the blend layer, which is not symmetric.
+    /// Per channel, multiplies where the base is below 0.5 and screens where it is above, which keeps the contrast of the base.
+    /// The result is clamped after combining the channels.
+    Overlay,
+}
+
+#[derive(Clone, Debug)]
+pub struct BlendNode {
+    pub option: BlendOptions,
+    /// How much of the blended result is used per channel, where 0 keeps the first input and 1 uses the blended result.
+    /// Weights of `[1.0, 1.0, 1.0]` blend every channel fully, for example `[1.0, 0.0, 0.0]` only blends the red channel.
+    pub weights: [f32; 3],
+}
+
+impl BlendNode {
+    /// Creates a node that blends every channel fully with the given mode.
+    pub fn new(option: BlendOptions) -> Self {
+        BlendNode { option, weights: [1.0, 1.0, 1.0] }
+    }
+}
+
+impl TextureTransformer<Rgb32FImage> for BlendNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image1 = inputs[0];
+        let image2 = inputs[1];
+        par_from_fn(image1.width(), image1.height(), |x, y| {
+            let pix1 = image1.get_pixel(x, y);
+            let pix2 = image2.get_pixel(x, y);
+            let blended = match self.option {
+                BlendOptions::Add => arithmetic::add(pix1, pix2),
+                BlendOptions::Subtract => arithmetic::sub(pix1, pix2),
+                BlendOptions::Multiply => arithmetic::mul(pix1, pix2),
+                BlendOptions::Mask(threshold, brightness) => if brightness.of(pix1) >= threshold {*pix2} else {Rgb([0.0, 0.0, 0.0])},
+                BlendOptions::MaskInverted(threshold, brightness) => if brightness.of(pix2) >= threshold {*pix1} else {Rgb([0.0, 0.0, 0.0])},
+                BlendOptions::Overlay => pix1.map2(pix2, |a, b| {
+                    let overlay = if a < 0.5 { 2.0 * a * b } else { 1.0 - 2.0 * (1.0 - a) * (1.0 - b) };
+                    overlay.clamp(0.0, 1.0)
+                }),
+            };
+            Rgb([0, 1, 2].map(|c| blended[c] * self.weights[c] + pix1[c] * (1.0 - self.weights[c])))
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn input_names(&self) -> Vec<Cow<'static, str>> {
+        vec![Cow::Borrowed("A"), Cow::Borrowed("B")]
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+
+    fn describe(&self) -> String {
+        if self.weights == [1.0, 1.0, 1.0] {
+            format!("Blend({:?})", self.option)
+        } else {
+            format!("Blend({:?}, weights {:?})", self.option, self.weights)
+        }
+    }
+}
+
+/// A node that remaps the values of an image through a tone curve, applied to each channel separately.
+#[derive(Clone, Debug)]
+pub struct CurveNode {
+    /// The control points of the curve as (input, output) pairs. The input values must be strictly increasing.
+    /// Values outside of the first and last control points are mapped to the output of that control point.
+    pub points: Vec<(f32, f32)>
+}
+
+impl CurveNode {
+    /// Checks whether the control points form a valid curve.
+    pub fn valid_points(&self) -> bool {
+        !self.points.is_empty() && self.points.windows(2).all(|w| w[0].0 < w[1].0)
+    }
+
+    /// Evaluates the curve by linearly interpolating between the control points.
+    pub fn apply(&self, value: f32) -> f32 {
+        let (first_x, first_y) = self.points[0];
+        if value <= first_x {
+            return first_y;
+        }
+        for w in self.points.windows(2) {
+            let (x0, y0) = w[0];
+            let (x1, y1) = w[1];
+            if value <= x1 {
+                let fraction = (value - x0) / (x1 - x0);
+                return y0 + (y1 - y0) * fraction;
+            }
+        }
+        self.points[self.points.len() - 1].1
+    }
+}
+
+impl TextureTransformer<Rgb32FImage> for CurveNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+            image.get_pixel(x, y).map(|v| self.apply(v))
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 1 && self.valid_points()
+    }
+
+    fn describe(&self) -> String {
+        format!("Curve({} points)", self.points.len())
+    }
+}
+
+/// The logical operation used to combine two masks. Masks are treated as continuous values, where 1 is fully set.
+#[derive(Clone, Debug)]
+pub enum MaskOp {
+    /// Set where both masks are set, the minimum of both values.
+    And,
+    /// Set where either mask is set, the maximum of both values.
+    Or,
+    /// Set where exactly one mask is set, the absolute difference of both values.
+    Xor,
+    /// Set where the first mask is set but the second is not.
+    Subtract,
+}
+
+/// A node that combines two masks per channel with a logical operator.
+#[derive(Clone, Debug)]
+pub struct MaskOpNode {
+    pub op: MaskOp
+}
+
+impl TextureTransformer<Rgb32FImage> for MaskOpNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image1 = inputs[0];
+        let image2 = inputs[1];
+        Rgb32FImage::from_fn(image1.width(), image1.height(), |x, y| {
+            let pix1 = image1.get_pixel(x, y);
+            let pix2 = image2.get_pixel(x, y);
+            match self.op {
+                MaskOp::And => pix1.map2(pix2, |a, b| a.min(b)),
+                MaskOp::Or => pix1.map2(pix2, |a, b| a.max(b)),
+                MaskOp::Xor => pix1.map2(pix2, |a, b| (a - b).abs()),
+                MaskOp::Subtract => arithmetic::sub(pix1, pix2),
+            }
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn input_names(&self) -> Vec<Cow<'static, str>> {
+        vec![Cow::Borrowed("A"), Cow::Borrowed("B")]
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+
+    fn describe(&self) -> String {
+        format!("MaskOp({:?})", self.op)
+    }
+}
+
+/// What sampling nodes read for positions outside of their input.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EdgeMode {
+    /// Uses the nearest pixel on the edge of the image.
+    Clamp,
+    /// Repeats the image, so positions past one edge continue at the opposite edge.
+    Wrap,
+    /// Mirrors the image at its edges, so the pixel just outside an edge equals the pixel on it.
+    Reflect,
+    /// Uses the given color for every position outside of the image.
+    Fill(Color),
+}
+
+/// Returns the pixel at a position, using the edge mode for positions outside of the image.
+pub fn pixel_at(image: &Rgb32FImage, x: i64, y: i64, mode: EdgeMode) -> Color {
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    let (x, y) = match mode {
+        EdgeMode::Clamp => (x.clamp(0, width - 1), y.clamp(0, height - 1)),
+        EdgeMode::Wrap => (x.rem_euclid(width), y.rem_euclid(height)),
+        EdgeMode::Reflect => (reflect(x, width), reflect(y, height)),
+        EdgeMode::Fill(color) => {
+            if x < 0 || y < 0 || x >= width || y >= height {
+                return color;
+            }
+            (x, y)
+        }
+    };
+    *image.get_pixel(x as u32, y as u32)
+}
+
+/// Mirrors a position into the range 0..size, repeating the edge pixels.
+fn reflect(position: i64, size: i64) -> i64 {
+    let period = position.rem_euclid(2 * size);
+    if period < size { period } else { 2 * size - 1 - period }
+}
+
+/// Samples an image at a fractional pixel position with bilinear filtering, where pixel centers lie on whole coordinates.
+/// Positions outside of the image are handled by the edge mode.
+pub fn sample(image: &Rgb32FImage, x: f32, y: f32, mode: EdgeMode) -> Color {
+    sample_bilinear(x, y, |px, py| pixel_at(image, px, py, mode))
+}
+
+/// Samples an image at a fractional pixel position with bilinear filtering.
+/// Pixels are read through the given function, which decides what happens at the edges of the image.
+fn sample_bilinear(x: f32, y: f32, pixel: impl Fn(i64, i64) -> Rgb<f32>) -> Rgb<f32> {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = x - x0;
+    let fy = y - y0;
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let top = pixel(x0, y0).map2(&pixel(x0 + 1, y0), |a, b| a + (b - a) * fx);
+    let bottom = pixel(x0, y0 + 1).map2(&pixel(x0 + 1, y0 + 1), |a, b| a + (b - a) * fx);
+    top.map2(&bottom, |a, b| a + (b - a) * fy)
+}
+
+/// Interpolates between p1 and p2 with a Catmull-Rom spline through four evenly spaced values.
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+    let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+    let c = -0.5 * p0 + 0.5 * p2;
+    ((a * t + b) * t + c) * t + p1
+}
+
+/// Samples an image at a fractional pixel position with Catmull-Rom bicubic filtering.
+/// Pixels are read through the given function, which decides what happens at the edges of the image.
+/// Like any Catmull-Rom filter, this can overshoot slightly around sharp edges.
+fn sample_bicubic(x: f32, y: f32, pixel: impl Fn(i64, i64) -> Rgb<f32>) -> Rgb<f32> {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = x - x0;
+    let fy = y - y0;
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let row = |py: i64| {
+        let [p0, p1, p2, p3] = [-1, 0, 1, 2].map(|dx| pixel(x0 + dx, py));
+        Rgb([0, 1, 2].map(|c| catmull_rom(p0.0[c], p1.0[c], p2.0[c], p3.0[c], fx)))
+    };
+    let [r0, r1, r2, r3] = [-1, 0, 1, 2].map(|dy| row(y0 + dy));
+    Rgb([0, 1, 2].map(|c| catmull_rom(r0.0[c], r1.0[c], r2.0[c], r3.0[c], fy)))
+}
+
+/// A node that pans and zooms into its input, so a pattern can be explored without changing its generator.
+/// The input is sampled with bilinear filtering.
+#[derive(Clone, Debug)]
+pub struct ViewTransformNode {
+    /// The offset of the view center, as a fraction of the image size.
+    pub offset: (f32, f32),
+    /// The magnification of the view. A zoom of 1 with no offset leaves the input unchanged.
+    pub zoom: f32,
+    /// What is shown outside of the input when zooming out or panning. Wrapping repeats the input like a tile.
+    pub edge_mode: EdgeMode,
+}
+
+impl TextureTransformer<Rgb32FImage> for ViewTransformNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        let width = image.width() as f32;
+        let height = image.height() as f32;
+        Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+            let u = ((x as f32 + 0.5) / width - 0.5) / self.zoom + 0.5 + self.offset.0;
+            let v = ((y as f32 + 0.5) / height - 0.5) / self.zoom + 0.5 + self.offset.1;
+            sample(image, u * width - 0.5, v * height - 0.5, self.edge_mode)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 1 && self.zoom > 0.0
+    }
+
+    fn describe(&self) -> String {
+        format!("ViewTransform(offset {:?}, zoom {}, {:?})", self.offset, self.zoom, self.edge_mode)
+    }
+}
+
+/// A node that sharpens its input by subtracting the four direct neighbours from each pixel, scaled by the amount.
+/// The output is clamped between 0 and 1.
+#[derive(Clone, Debug)]
+pub struct SharpenNode {
+    /// The strength of the sharpening. An amount of 0 leaves the input unchanged.
+    pub amount: f32,
+    /// What is read for the neighbours of pixels on the edge of the image.
+    pub edge_mode: EdgeMode,
+}
+
+impl TextureTransformer<Rgb32FImage> for SharpenNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        let center = 1.0 + 4.0 * self.amount;
+        Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+            let (x, y) = (x as i64, y as i64);
+            let neighbours = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .map(|(nx, ny)| pixel_at(image, nx, ny, self.edge_mode));
+            let mut pix = pixel_at(image, x, y, self.edge_mode).map(|v| v * center);
+            for neighbour in neighbours {
+                pix = pix.map2(&neighbour, |a, b| a - self.amount * b);
+            }
+            pix.map(|v| v.clamp(0.0, 1.0))
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("Sharpen({})", self.amount)
+    }
+}
+
+/// A node that blurs its input along a single direction, which gives streaks like brushed metal.
+/// Each pixel is the average of `length` samples on a line through it.
+#[derive(Clone, Debug)]
+pub struct MotionBlurNode {
+    /// The direction of the blur in radians, where 0 blurs horizontally and positive angles rotate towards the bottom.
+    pub angle: f32,
+    /// The number of pixels that are averaged. A length of 1 leaves the input unchanged.
+    pub length: u32,
+    /// What is read for samples that fall outside of the image.
+    pub edge_mode: EdgeMode,
+}
+
+impl TextureTransformer<Rgb32FImage> for MotionBlurNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        let length = self.length.max(1);
+        let (dy, dx) = self.angle.sin_cos();
+        let offsets: Vec<(i64, i64)> = (0..length)
+            .map(|i| {
+                let t = i as f32 - (length - 1) as f32 / 2.0;
+                ((t * dx).round() as i64, (t * dy).round() as i64)
+            })
+            .collect();
+        par_from_fn(image.width(), image.height(), |x, y| {
+            let sum = offsets.iter()
+                .map(|(ox, oy)| pixel_at(image, x as i64 + ox, y as i64 + oy, self.edge_mode))
+                .fold(Rgb([0.0, 0.0, 0.0]), |acc, pix| acc.map2(&pix, |a, b| a + b));
+            sum.map(|v| v / length as f32)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("MotionBlur(angle {}, length {})", self.angle, self.length)
+    }
+}
+
+/// A node that blurs its input by averaging a square of pixels around every pixel.
+/// With `EdgeMode::Wrap` the blur reads across the opposite edge, so a tileable input stays tileable.
+#[derive(Clone, Debug)]
+pub struct BoxBlurNode {
+    /// The distance from a pixel to the edge of the square it averages. A radius of 0 leaves the input unchanged.
+    pub radius: u32,
+    /// What is read for samples that fall outside of the image.
+    pub edge_mode: EdgeMode,
+}
+
+impl BoxBlurNode {
+    /// Averages the pixels within the radius along one axis, as the blur is separable.
+    fn blur_pass(&self, image: &Rgb32FImage, (dx, dy): (i64, i64)) -> Rgb32FImage {
+        let radius = self.radius as i64;
+        par_from_fn(image.width(), image.height(), |x, y| {
+            let sum = (-radius..=radius)
+                .map(|i| pixel_at(image, x as i64 + i * dx, y as i64 + i * dy, self.edge_mode))
+                .fold(Rgb([0.0, 0.0, 0.0]), |acc, pix| acc.map2(&pix, |a, b| a + b));
+            sum.map(|v| v / (2 * radius + 1) as f32)
+        })
+    }
+}
+
+impl TextureTransformer<Rgb32FImage> for BoxBlurNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let horizontal = self.blur_pass(inputs[0], (1, 0));
+        self.blur_pass(&horizontal, (0, 1))
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("BoxBlur(radius {}, {:?})", self.radius, self.edge_mode)
+    }
+}
+
+/// The way a DitherNode spreads the quantization error over neighbouring pixels.
+#[derive(Clone, Debug)]
+pub enum DitherMethod {
+    /// Offsets each pixel by a threshold from a repeating 4x4 Bayer matrix before quantizing.
+    Ordered,
+    /// Diffuses the quantization error of each pixel to the pixels right of and below it.
+    FloydSteinberg,
+}
+
+/// The 4x4 Bayer matrix used for ordered dithering, with values from 0 to 15.
+const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+/// A node that quantizes each channel to a number of evenly spaced levels, while dithering to hide banding.
+/// Exporting to an 8-bit image is a quantization to 256 levels, so dithering first keeps smooth gradients smooth.
+#[derive(Clone, Debug)]
+pub struct DitherNode {
+    /// The number of levels each channel is quantized to, including 0 and 1. Must be at least 2,
+    /// fewer levels are treated as 2 so a node that is still being edited does not panic.
+    pub levels: u32,
+    pub method: DitherMethod,
+}
+
+impl DitherNode {
+    /// The number of steps between the levels, at least 1.
+    fn steps(&self) -> f32 {
+        (self.levels.max(2) - 1) as f32
+    }
+
+    fn quantize(&self, value: f32) -> f32 {
+        let steps = self.steps();
+        ((value * steps).round() / steps).clamp(0.0, 1.0)
+    }
+}
+
+impl TextureTransformer<Rgb32FImage> for DitherNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        let steps = self.steps();
+        match self.method {
+            DitherMethod::Ordered => Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+                let threshold = (BAYER_4X4[(y % 4) as usize][(x % 4) as usize] + 0.5) / 16.0 - 0.5;
+                image.get_pixel(x, y).map(|v| self.quantize(v + threshold / steps))
+            }),
+            DitherMethod::FloydSteinberg => {
+                let width = image.width() as usize;
+                let height = image.height() as usize;
+                let mut values: Vec<f32> = image.as_raw().clone();
+                for y in 0..height {
+                    for x in 0..width {
+                        for c in 0..3 {
+                            let i = (y * width + x) * 3 + c;
+                            let old = values[i];
+                            let new = self.quantize(old);
+                            values[i] = new;
+                            let error = old - new;
+                            let mut spread = |dx: isize, dy: usize, weight: f32| {
+                                let nx = x as isize + dx;
+                                if nx >= 0 && (nx as usize) < width && y + dy < height {
+                                    values[((y + dy) * width + nx as usize) * 3 + c] += error * weight;
+                                }
+                            };
+                            spread(1, 0, 7.0 / 16.0);
+                            spread(-1, 1, 3.0 / 16.0);
+                            spread(0, 1, 5.0 / 16.0);
+                            spread(1, 1, 1.0 / 16.0);
+                        }
+                    }
+                }
+                Rgb32FImage::from_raw(image.width(), image.height(), values).unwrap()
+            }
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 1 && self.levels >= 2
+    }
+
+    fn describe(&self) -> String {
+        format!("Dither({} levels, {:?})", self.levels, self.method)
+    }
+}
+
+/// A node that applies a function to every pixel of its input, for quick custom nodes without a new struct.
+#[derive(Clone)]
+pub struct PixelMapNode<F> {
+    pub f: F,
+    /// Identifies the values captured by `f`, which can not be inspected. Must be changed whenever those values change,
+    /// or results generated with the old values are kept.
+    pub key: u64,
+}
+
+impl<F: Fn(Color) -> Color + Clone + Send + 'static> TextureTransformer<Rgb32FImage> for PixelMapNode<F> {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        Rgb32FImage::from_fn(image.width(), image.height(), |x, y| (self.f)(*image.get_pixel(x, y)))
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(&(std::any::TypeId::of::<F>(), self.key))
+    }
+}
+
+/// A node that combines the pixels of two inputs with a function, for custom blends without extending `BlendOptions`.
+#[derive(Clone)]
+pub struct PixelMap2Node<F> {
+    pub f: F,
+    /// Identifies the values captured by `f`, like `PixelMapNode::key`.
+    pub key: u64,
+}
+
+impl<F: Fn(Color, Color) -> Color + Clone + Send + 'static> TextureTransformer<Rgb32FImage> for PixelMap2Node<F> {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image1 = inputs[0];
+        let image2 = inputs[1];
+        Rgb32FImage::from_fn(image1.width(), image1.height(), |x, y| {
+            (self.f)(*image1.get_pixel(x, y), *image2.get_pixel(x, y))
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(&(std::any::TypeId::of::<F>(), self.key))
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+}
+
+/// The filter a ScaleNode uses to sample its input.
+#[derive(Clone, Copy, Debug)]
+pub enum ScaleFilter {
+    /// Uses the closest pixel, which keeps hard edges.
+    Nearest,
+    /// Interpolates linearly between the four closest pixels.
+    Bilinear,
+    /// Interpolates with a Catmull-Rom spline through the sixteen closest pixels, which is smoother than bilinear filtering.
+    Bicubic,
+}
+
+/// A node that resizes its input to the given dimensions.
+#[derive(Clone, Debug)]
+pub struct ScaleNode {
+    pub width: u32,
+    pub height: u32,
+    pub filter: ScaleFilter,
+    /// What the filter reads for pixels just outside of the input.
+    pub edge_mode: EdgeMode,
+}
+
+impl TextureTransformer<Rgb32FImage> for ScaleNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        let scale_x = image.width() as f32 / self.width as f32;
+        let scale_y = image.height() as f32 / self.height as f32;
+        let pixel = |px: i64, py: i64| pixel_at(image, px, py, self.edge_mode);
+        Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let u = (x as f32 + 0.5) * scale_x - 0.5;
+            let v = (y as f32 + 0.5) * scale_y - 0.5;
+            match self.filter {
+                ScaleFilter::Nearest => pixel(u.round() as i64, v.round() as i64),
+                ScaleFilter::Bilinear => sample_bilinear(u, v, pixel),
+                ScaleFilter::Bicubic => sample_bicubic(u, v, pixel),
+            }
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 1 && self.width > 0 && self.height > 0
+    }
+
+    fn output_size(&self, _input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        (self.width > 0 && self.height > 0).then_some((self.width, self.height))
+    }
+
+    fn describe(&self) -> String {
+        format!("Scale({}x{}, {:?})", self.width, self.height, self.filter)
+    }
+}
+
+/// A node that takes the per-channel minimum of two inputs.
+#[derive(Clone, Debug)]
+pub struct MinNode {}
+
+impl TextureTransformer<Rgb32FImage> for MinNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image1 = inputs[0];
+        let image2 = inputs[1];
+        Rgb32FImage::from_fn(image1.width(), image1.height(), |x, y| {
+            image1.get_pixel(x, y).map2(image2.get_pixel(x, y), f32::min)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+
+    fn describe(&self) -> String {
+        String::from("Min")
+    }
+}
+
+/// A node that takes the per-channel maximum of two inputs.
+#[derive(Clone, Debug)]
+pub struct MaxNode {}
+
+impl TextureTransformer<Rgb32FImage> for MaxNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image1 = inputs[0];
+        let image2 = inputs[1];
+        Rgb32FImage::from_fn(image1.width(), image1.height(), |x, y| {
+            image1.get_pixel(x, y).map2(image2.get_pixel(x, y), f32::max)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+
+    fn describe(&self) -> String {
+        String::from("Max")
+    }
+}
+
+/// A node that linearly interpolates between two inputs.
+#[derive(Clone, Debug)]
+pub struct LerpNode {
+    /// The fraction of the second input in the output, clamped between 0 and 1. A fraction of 0 outputs the first input.
+    pub t: f32,
+}
+
+impl TextureTransformer<Rgb32FImage> for LerpNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image1 = inputs[0];
+        let image2 = inputs[1];
+        Rgb32FImage::from_fn(image1.width(), image1.height(), |x, y| {
+            arithmetic::mix(image1.get_pixel(x, y), image2.get_pixel(x, y), self.t)
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn input_names(&self) -> Vec<Cow<'static, str>> {
+        vec![Cow::Borrowed("from"), Cow::Borrowed("to")]
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+
+    fn describe(&self) -> String {
+        format!("Lerp({})", self.t)
+    }
+}
+
+/// The operation a CombineNode applies across all of its inputs.
+#[derive(Clone, Debug)]
+pub enum CombineOp {
+    /// The sum of all inputs, clamped between 0 and 1.
+    Sum,
+    Average,
+    Max,
+    Min,
+}
+
+/// A node that combines any amount of inputs per pixel and channel, so many layers can be blended without chaining nodes.
+#[derive(Clone, Debug)]
+pub struct CombineNode {
+    /// The amount of inputs of the node.
+    pub count: usize,
+    pub op: CombineOp,
+}
+
+impl TextureTransformer<Rgb32FImage> for CombineNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let first = inputs[0];
+        Rgb32FImage::from_fn(first.width(), first.height(), |x, y| {
+            let pixels = inputs.iter().map(|image| *image.get_pixel(x, y));
+            match self.op {
+                CombineOp::Sum => arithmetic::clamp01(&pixels.reduce(|a, b| a.map2(&b, |a, b| a + b)).unwrap()),
+                CombineOp::Average => pixels.reduce(|a, b| a.map2(&b, |a, b| a + b)).unwrap().map(|v| v / inputs.len() as f32),
+                CombineOp::Max => pixels.reduce(|a, b| a.map2(&b, f32::max)).unwrap(),
+                CombineOp::Min => pixels.reduce(|a, b| a.map2(&b, f32::min)).unwrap(),
+            }
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        self.count
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        !inputs.is_empty() && inputs.len() == self.count && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+
+    fn describe(&self) -> String {
+        format!("Combine({:?} of {} inputs)", self.op, self.count)
+    }
+}
+
+/// A node that turns a color texture into a grayscale mask of its luminance, for example to drive the Mask blend mode.
+#[derive(Clone, Debug)]
+pub struct LuminanceToMaskNode {
+    /// Outputs one minus the luminance, so dark areas of the input become set in the mask.
+    pub invert: bool,
+}
+
+impl TextureTransformer<Rgb32FImage> for LuminanceToMaskNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+            let value = Brightness::Luminance.of(image.get_pixel(x, y));
+            let value = if self.invert { 1.0 - value } else { value };
+            Rgb([value, value, value])
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("LuminanceToMask({})", if self.invert { "inverted" } else { "normal" })
+    }
+}
+
+/// A node that cuts a rectangular region out of its input, to pick out a detail before further processing.
+/// The region is clamped to the bounds of the input, and the node is invalid if nothing of the region remains.
+#[derive(Clone, Debug)]
+pub struct CropNode {
+    /// The left edge of the region.
+    pub x: u32,
+    /// The top edge of the region.
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropNode {
+    /// Returns the region clamped to an input of the given size, as (x, y, width, height).
+    fn region(&self, (width, height): (u32, u32)) -> (u32, u32, u32, u32) {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        (x, y, self.width.min(width - x), self.height.min(height - y))
+    }
+}
+
+impl TextureTransformer<Rgb32FImage> for CropNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (x, y, width, height) = self.region(inputs[0].dimensions());
+        imageops::crop_imm(inputs[0], x, y, width, height).to_image()
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 1 && self.output_size(&[inputs[0].dimensions()]).is_some()
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        let (_, _, width, height) = self.region(*input_sizes.first()?);
+        (width > 0 && height > 0).then_some((width, height))
+    }
+
+    fn describe(&self) -> String {
+        format!("Crop({}x{} at {}, {})", self.width, self.height, self.x, self.y)
+    }
+}
+
+/// A node that enlarges the canvas of its input, placing the input inside a border of a fill color.
+/// Padding before rotating or blurring keeps the edges of the input from being clipped or smeared.
+#[derive(Clone, Debug)]
+pub struct PadNode {
+    /// The width of the border on the left side, in pixels.
+    pub left: u32,
+    /// The width of the border on the right side, in pixels.
+    pub right: u32,
+    /// The height of the border above the input, in pixels.
+    pub top: u32,
+    /// The height of the border below the input, in pixels.
+    pub bottom: u32,
+    /// The color of the border.
+    pub fill: Color,
+}
+
+impl TextureTransformer<Rgb32FImage> for PadNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        // Borders that overflow the size of an image are rejected by is_valid, the input is passed through as is.
+        let Some((width, height)) = self.output_size(&[image.dimensions()]) else {
+            return image.clone();
+        };
+        let mut padded = Rgb32FImage::from_pixel(width, height, self.fill);
+        imageops::replace(&mut padded, image, self.left as i64, self.top as i64);
+        padded
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 1 && self.output_size(&[inputs[0].dimensions()]).is_some()
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        let (width, height) = *input_sizes.first()?;
+        let width = width.checked_add(self.left)?.checked_add(self.right)?;
+        let height = height.checked_add(self.top)?.checked_add(self.bottom)?;
+        Some((width, height))
+    }
+
+    fn describe(&self) -> String {
+        format!("Pad(left {}, right {}, top {}, bottom {}, {})", self.left, self.right, self.top, self.bottom, color_to_hex(&self.fill))
+    }
+}
+
+/// How a NormalBlendNode combines two normal maps.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalBlend {
+    /// Averages the normals and renormalizes the result, which flattens both maps somewhat.
+    Linear,
+    /// Rotates the detail normals of the second input onto the surface of the first input, keeping the detail intact.
+    ReorientedNormalMapping,
+}
+
+/// Decodes a pixel of a normal map, with channels between 0 and 1, to a vector with components between -1 and 1.
+fn decode_normal(pixel: &Color) -> [f32; 3] {
+    pixel.0.map(|v| v * 2.0 - 1.0)
+}
+
+/// Normalizes a vector and encodes it as a pixel of a normal map. Vectors of length zero become the neutral normal.
+fn encode_normal([x, y, z]: [f32; 3]) -> Color {
+    let length = (x * x + y * y + z * z).sqrt();
+    if length == 0.0 {
+        return Rgb([0.5, 0.5, 1.0]);
+    }
+    Rgb([x, y, z].map(|v| v / length * 0.5 + 0.5))
+}
+
+/// A node that layers two tangent space normal maps, such as a detail map on top of a base map.
+/// Normal maps can not be blended like colors, as the result would no longer be a unit vector.
+#[derive(Clone, Debug)]
+pub struct NormalBlendNode {
+    pub method: NormalBlend,
+}
+
+impl TextureTransformer<Rgb32FImage> for NormalBlendNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let (base, detail) = (inputs[0], inputs[1]);
+        Rgb32FImage::from_fn(base.width(), base.height(), |x, y| {
+            let [bx, by, bz] = decode_normal(base.get_pixel(x, y));
+            let [dx, dy, dz] = decode_normal(detail.get_pixel(x, y));
+            match self.method {
+                NormalBlend::Linear => encode_normal([bx + dx, by + dy, bz + dz]),
+                NormalBlend::ReorientedNormalMapping => {
+                    let (tx, ty, tz) = (bx, by, bz + 1.0);
+                    let (ux, uy, uz) = (-dx, -dy, dz);
+                    let scale = (tx * ux + ty * uy + tz * uz) / tz;
+                    encode_normal([tx * scale - ux, ty * scale - uy, tz * scale - uz])
+                },
+            }
+        })
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn input_names(&self) -> Vec<Cow<'static, str>> {
+        vec![Cow::Borrowed("base"), Cow::Borrowed("detail")]
+    }
+
+    fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
+        inputs.len() == 2 && all_same_size(inputs)
+    }
+
+    fn output_size(&self, input_sizes: &[(u32, u32)]) -> Option<(u32, u32)> {
+        same_size_output(input_sizes)
+    }
+
+    fn describe(&self) -> String {
+        format!("NormalBlend({:?})", self.method)
+    }
 }
 
-pub struct BlendNode {
-    pub option: BlendOptions
+/// A node that makes bright areas glow, by blurring the pixels brighter than a threshold and adding them back onto the input.
+#[derive(Clone, Debug)]
+pub struct BloomNode {
+    /// The luminance a pixel must exceed to glow.
+    pub threshold: f32,
+    /// The standard deviation of the blur in pixels, which sets how far the glow spreads. A radius of 0 does not blur.
+    pub radius: f32,
+    /// The strength of the glow that is added. The result is clamped between 0 and 1.
+    pub intensity: f32,
 }
 
-impl TextureTransformer<Rgb32FImage> for BlendNode {
+impl TextureTransformer<Rgb32FImage> for BloomNode {
     fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
-        let image1 = inputs[0];
-        let image2 = inputs[1];
-        Rgb32FImage::from_fn(image1.width(), image1.height(), |x, y| {
-            let pix1 = image1.get_pixel(x, y);
-            let pix2 = image2.get_pixel(x, y);
-            match self.option {
-                BlendOptions::Add => pix1.map2(pix2, |a, b| (a + b).clamp(0.0, 1.0)),
-                BlendOptions::Subtract => pix1.map2(pix2, |a, b| (a - b).clamp(0.0, 1.0)),
-                BlendOptions::Multiply => pix1.map2(pix2, |a, b| (a * b).clamp(0.0, 1.0)),
-                BlendOptions::Mask(threshold) => if average_color(pix1) >= threshold {*pix2} else {Rgb([0.0, 0.0, 0.0])}
-            }
+        let image = inputs[0];
+        let bright = Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+            let pix = image.get_pixel(x, y);
+            if Brightness::Luminance.of(pix) > self.threshold { *pix } else { Rgb([0.0, 0.0, 0.0]) }
+        });
+        let glow = if self.radius > 0.0 { imageops::blur(&bright, self.radius) } else { bright };
+        Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+            let pix = image.get_pixel(x, y).map2(glow.get_pixel(x, y), |a, b| a + b * self.intensity);
+            arithmetic::clamp01(&pix)
         })
     }
 
     fn inputs(&self) -> usize {
-        2
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("Bloom(threshold {}, radius {}, intensity {})", self.threshold, self.radius, self.intensity)
+    }
+}
+
+/// A node that recolors the region connected to a seed pixel, like the bucket fill of a paint program.
+/// The region spreads to horizontally and vertically neighbouring pixels whose color is close to the color of the seed.
+#[derive(Clone, Debug)]
+pub struct FloodRecolorNode {
+    /// The largest difference in any channel from the color of the seed for a pixel to be part of the region.
+    pub tolerance: f32,
+    /// The color the region is replaced with.
+    pub replacement: Color,
+    /// The pixel the region starts from. The node is invalid if it lies outside of the input.
+    pub seed: (u32, u32),
+}
+
+impl TextureTransformer<Rgb32FImage> for FloodRecolorNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        let (width, height) = image.dimensions();
+        let target = *image.get_pixel(self.seed.0, self.seed.1);
+        let matches = |x: u32, y: u32| image.get_pixel(x, y).0.iter().zip(target.0).all(|(a, b)| (a - b).abs() <= self.tolerance);
+        let mut result = image.clone();
+        let mut visited = vec![false; (width * height) as usize];
+        let mut stack = vec![self.seed];
+        visited[(self.seed.1 * width + self.seed.0) as usize] = true;
+        while let Some((x, y)) = stack.pop() {
+            result.put_pixel(x, y, self.replacement);
+            let neighbours = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+            for (nx, ny) in neighbours {
+                if nx < width && ny < height && !visited[(ny * width + nx) as usize] && matches(nx, ny) {
+                    visited[(ny * width + nx) as usize] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        result
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
     }
 
     fn is_valid(&self, inputs: &Vec<&Rgb32FImage>) -> bool {
-        inputs.len() == 2
-            && inputs[0].width() == inputs[1].width()
-            && inputs[0].height() == inputs[1].height()
+        !inputs.is_empty() && self.seed.0 < inputs[0].width() && self.seed.1 < inputs[0].height()
+    }
+
+    fn describe(&self) -> String {
+        format!("FloodRecolor(from {:?}, tolerance {}, to {})", self.seed, self.tolerance, color_to_hex(&self.replacement))
+    }
+}
+
+/// A node that fills an image of the size of its input with the mean color of the input, to extract its overall tint.
+#[derive(Clone, Debug)]
+pub struct AverageColorNode {}
+
+impl TextureTransformer<Rgb32FImage> for AverageColorNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        let image = inputs[0];
+        Rgb32FImage::from_pixel(image.width(), image.height(), mean_color(image))
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn describe(&self) -> String {
+        String::from("AverageColor")
+    }
+}
+
+/// A node that passes its input through unchanged, and reports the minimum, maximum and mean of every channel of it
+/// through `TextureGraph::probe_stats`, for example to level a texture automatically or to debug a graph.
+#[derive(Clone, Debug)]
+pub struct ProbeNode {}
+
+impl TextureTransformer<Rgb32FImage> for ProbeNode {
+    fn generate(&self, inputs: Vec<&Rgb32FImage>) -> Rgb32FImage {
+        inputs[0].clone()
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn params_hash(&self) -> u64 {
+        hash_debug(self)
+    }
+
+    fn probe(&self, output: &Rgb32FImage) -> Option<ChannelStats> {
+        if output.width() == 0 || output.height() == 0 {
+            return None;
+        }
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for pix in output.pixels() {
+            for c in 0..3 {
+                min[c] = min[c].min(pix[c]);
+                max[c] = max[c].max(pix[c]);
+            }
+        }
+        Some(ChannelStats { min: min.to_vec(), max: max.to_vec(), mean: mean_color(output).0.to_vec() })
+    }
+
+    fn describe(&self) -> String {
+        String::from("Probe")
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use graph::TextureTransformer;
-    use image::Rgb;
-    use core::Gradient;
+    use graph::{GraphError, Node, TextureGraph, TextureTransformer, CloneTransformer};
+    use image::{Rgb, Rgb32FImage};
+    use core::{Brightness, Color, Gradient};
 
-    use crate::{SolidColorNode, BlendNode, BlendOptions, GradientNode, GradientNodeDirection, nodes::generators::GeneratorProperties};
+    use crate::{ProbeNode, AverageColorNode, BoxBlurNode, FloodRecolorNode, BloomNode, NormalBlendNode, NormalBlend, PadNode, CropNode, LuminanceToMaskNode, SolidColorNode, BlendNode, BlendOptions, CurveNode, MaskOp, MaskOpNode, ViewTransformNode, SharpenNode, MotionBlurNode, DitherNode, DitherMethod, PixelMapNode, PixelMap2Node, ScaleNode, ScaleFilter, all_same_size, MinNode, MaxNode, LerpNode, CombineNode, CombineOp, EdgeMode, sample, GradientNode, GradientNodeDirection, nodes::generators::GeneratorProperties};
 
     #[test]
     fn test_blend_add() {
         let node1 = SolidColorNode{color: Rgb([1.0, 0.0, 0.2]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([0.0, 1.0, 0.4]), properties: GeneratorProperties::default()};
-        let blend_node = BlendNode{option: BlendOptions::Add};
+        let blend_node = BlendNode::new(BlendOptions::Add);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         let targets = vec![&image1, &image2];
@@ -61,11 +1214,44 @@ mod tests {
         assert!(image.pixels().all(|pix| *pix == Rgb([1.0, 1.0, 0.6])))
     }
 
+    #[test]
+    fn test_blend_weights() {
+        let image1 = Rgb32FImage::from_pixel(8, 8, Rgb([0.2, 0.3, 0.4]));
+        let image2 = Rgb32FImage::from_pixel(8, 8, Rgb([0.5, 0.5, 0.5]));
+        let red_only = BlendNode{option: BlendOptions::Add, weights: [1.0, 0.0, 0.0]};
+        assert!(red_only.generate(vec![&image1, &image2]).pixels().all(|pix| *pix == Rgb([0.7, 0.3, 0.4])));
+        assert_ne!(BlendNode::new(BlendOptions::Add).params_hash(), red_only.params_hash());
+        let half = BlendNode{option: BlendOptions::Multiply, weights: [0.5, 0.5, 0.5]};
+        let image = half.generate(vec![&image1, &image2]);
+        assert!(image.pixels().all(|pix| pix.0.iter().zip([0.15, 0.225, 0.3]).all(|(a, b)| (a - b).abs() < 1e-6)));
+    }
+
+    #[test]
+    fn test_blend_clone() {
+        let node1 = SolidColorNode{color: Rgb([1.0, 0.0, 0.2]), properties: GeneratorProperties::default()};
+        let node2 = SolidColorNode{color: Rgb([0.0, 1.0, 0.4]), properties: GeneratorProperties::default()};
+        let blend_node = BlendNode::new(BlendOptions::Mask(0.3, Brightness::Luminance));
+        let image1 = node1.generate(vec![]);
+        let image2 = node2.generate(vec![]);
+        let copy = blend_node.clone_box();
+        assert_eq!(blend_node.generate(vec![&image1, &image2]), copy.generate(vec![&image1, &image2]));
+        assert_eq!(blend_node.inputs(), copy.inputs());
+    }
+
+    #[test]
+    fn test_describe() {
+        assert!(BlendNode::new(BlendOptions::Add).describe().contains("Add"));
+        let gradient = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 0.0, 0.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
+        assert_eq!("Gradient(horizontal, clamp x1, #000000→#ff0000)", gradient.describe());
+        // Nodes without parameters to describe fall back to the name of their type.
+        assert_eq!("PixelMapNode", PixelMapNode{f: |pix: Color| pix, key: 0}.describe());
+    }
+
     #[test]
     fn test_blend_add_bounds() {
         let node1 = SolidColorNode{color: Rgb([1.0, 0.0, 0.2]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([1.0, 1.0, 0.0]), properties: GeneratorProperties::default()};
-        let blend_node = BlendNode{option: BlendOptions::Add};
+        let blend_node = BlendNode::new(BlendOptions::Add);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         let targets = vec![&image1, &image2];
@@ -77,7 +1263,7 @@ mod tests {
     fn test_blend_subtract() {
         let node1 = SolidColorNode{color: Rgb([1.0, 0.8, 0.2]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([1.0, 0.4, 0.0]), properties: GeneratorProperties::default()};
-        let blend_node = BlendNode{option: BlendOptions::Subtract};
+        let blend_node = BlendNode::new(BlendOptions::Subtract);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         let targets = vec![&image1, &image2];
@@ -89,7 +1275,7 @@ mod tests {
     fn test_blend_subtract_bounds() {
         let node1 = SolidColorNode{color: Rgb([0.0, 0.8, 0.2]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([1.0, 0.4, 0.5]), properties: GeneratorProperties::default()};
-        let blend_node = BlendNode{option: BlendOptions::Subtract};
+        let blend_node = BlendNode::new(BlendOptions::Subtract);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         let targets = vec![&image1, &image2];
@@ -101,7 +1287,7 @@ mod tests {
     fn test_blend_multiply() {
         let node1 = SolidColorNode{color: Rgb([0.0, 0.2, 0.4]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([1.0, 2.0, 1.5]), properties: GeneratorProperties::default()};
-        let blend_node = BlendNode{option: BlendOptions::Multiply};
+        let blend_node = BlendNode::new(BlendOptions::Multiply);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         let targets = vec![&image1, &image2];
@@ -113,7 +1299,7 @@ mod tests {
     fn test_blend_multiply_bounds() {
         let node1 = SolidColorNode{color: Rgb([0.0, 0.2, 0.4]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([1.0, 2.0, 3.0]), properties: GeneratorProperties::default()};
-        let blend_node = BlendNode{option: BlendOptions::Multiply};
+        let blend_node = BlendNode::new(BlendOptions::Multiply);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         let targets = vec![&image1, &image2];
@@ -124,12 +1310,9 @@ mod tests {
 
     #[test]
     fn test_blend_mask() {
-        let node1 = GradientNode{
-            gradient: Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, 
-            direction: GradientNodeDirection::VERTICAL, 
-            properties: GeneratorProperties::default() };
+        let node1 = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, GradientNodeDirection::VERTICAL, GeneratorProperties::default());
         let node2 = SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::default()};
-        let blend_node = BlendNode{option: BlendOptions::Mask(0.4)};
+        let blend_node = BlendNode::new(BlendOptions::Mask(0.4, Brightness::Average));
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         let targets = vec![&image1, &image2];
@@ -145,11 +1328,79 @@ mod tests {
         }))
     }
 
+    #[test]
+    fn test_blend_mask_inverted() {
+        let node1 = SolidColorNode{color: Rgb([0.0, 0.0, 1.0]), properties: GeneratorProperties::default()};
+        let node2 = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
+        let blend_node = BlendNode::new(BlendOptions::MaskInverted(0.6, Brightness::Average));
+        let image1 = node1.generate(vec![]);
+        let image2 = node2.generate(vec![]);
+        let targets = vec![&image1, &image2];
+        let image = blend_node.generate(targets);
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            let mask = image2.get_pixel(x, y).0;
+            let threshold = (mask[0] + mask[1] + mask[2]) / 3.0;
+            if threshold >= 0.6 {
+                pix == &Rgb([0.0, 0.0, 1.0])
+            } else {
+                pix == &Rgb([0.0, 0.0, 0.0])
+            }
+        }));
+        assert_eq!(&Rgb([0.0, 0.0, 0.0]), image.get_pixel(0, 0));
+        assert_eq!(&Rgb([0.0, 0.0, 1.0]), image.get_pixel(image.width() - 1, 0));
+    }
+
+    #[test]
+    fn test_blend_mask_luminance() {
+        let node1 = SolidColorNode{color: Rgb([0.0, 1.0, 0.0]), properties: GeneratorProperties::default()};
+        let node2 = SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::default()};
+        let image1 = node1.generate(vec![]);
+        let image2 = node2.generate(vec![]);
+        let average = BlendNode::new(BlendOptions::Mask(0.5, Brightness::Average)).generate(vec![&image1, &image2]);
+        assert!(average.pixels().all(|pix| *pix == Rgb([0.0, 0.0, 0.0])));
+        let luminance = BlendNode::new(BlendOptions::Mask(0.5, Brightness::Luminance)).generate(vec![&image1, &image2]);
+        assert!(luminance.pixels().all(|pix| *pix == Rgb([1.0, 0.0, 0.0])));
+    }
+
+    #[test]
+    fn test_blend_overlay() {
+        let base = Rgb32FImage::from_pixel(4, 4, Rgb([0.25, 0.75, 0.9]));
+        let blend = Rgb32FImage::from_pixel(4, 4, Rgb([0.5, 0.5, 0.25]));
+        let blend_node = BlendNode::new(BlendOptions::Overlay);
+        let image = blend_node.generate(vec![&base, &blend]);
+        // Below 0.5 the base is multiplied, above it is screened.
+        let expected = [2.0 * 0.25 * 0.5, 1.0 - 2.0 * 0.25 * 0.5, 1.0 - 2.0 * 0.1 * 0.75];
+        assert!(image.pixels().all(|pix| pix.0.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-6)));
+        // Overlay is not symmetric, as the second input is never used as the base.
+        let swapped = blend_node.generate(vec![&blend, &base]);
+        assert!((swapped.get_pixel(0, 0).0[2] - 2.0 * 0.25 * 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_blend_overlay_bounds() {
+        let base = Rgb32FImage::from_pixel(4, 4, Rgb([0.2, 0.5, 1.5]));
+        let blend = Rgb32FImage::from_pixel(4, 4, Rgb([3.0, -1.0, 0.5]));
+        let image = BlendNode::new(BlendOptions::Overlay).generate(vec![&base, &blend]);
+        assert!(image.pixels().all(|pix| *pix == Rgb([1.0, 0.0, 1.0])));
+    }
+
+    #[test]
+    fn test_all_same_size() {
+        let image1 = Rgb32FImage::new(64, 32);
+        let image2 = Rgb32FImage::new(64, 32);
+        let image3 = Rgb32FImage::new(32, 64);
+        assert!(all_same_size(&[]));
+        assert!(all_same_size(&[&image1]));
+        assert!(all_same_size(&[&image1, &image2]));
+        assert!(!all_same_size(&[&image1, &image3]));
+        assert!(!all_same_size(&[&image1, &image2, &image3]));
+    }
+
     #[test]
     fn test_invalid_width() {
         let node1 = SolidColorNode{color: Rgb([0.0, 0.0, 0.0]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([0.0, 0.0, 0.0]), properties: GeneratorProperties { width: 64, height: 128 }};
-        let blend_node = BlendNode{option: BlendOptions::Add};
+        let blend_node = BlendNode::new(BlendOptions::Add);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         assert!(!blend_node.is_valid(&vec![&image1, &image2]));
@@ -159,10 +1410,548 @@ mod tests {
     fn test_invalid_height() {
         let node1 = SolidColorNode{color: Rgb([0.0, 0.0, 0.0]), properties: GeneratorProperties::default()};
         let node2 = SolidColorNode{color: Rgb([0.0, 0.0, 0.0]), properties: GeneratorProperties { width: 128, height: 64 }};
-        let blend_node = BlendNode{option: BlendOptions::Add};
+        let blend_node = BlendNode::new(BlendOptions::Add);
         let image1 = node1.generate(vec![]);
         let image2 = node2.generate(vec![]);
         assert!(!blend_node.is_valid(&vec![&image1, &image2]));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_curve_identity() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 0.5, 0.25]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
+        let curve_node = CurveNode{points: vec![(0.0, 0.0), (1.0, 1.0)]};
+        let image1 = node.generate(vec![]);
+        assert!(curve_node.is_valid(&vec![&image1]));
+        let image = curve_node.generate(vec![&image1]);
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            pix.0.iter().zip(image1.get_pixel(x, y).0).all(|(a, b)| (a - b).abs() < 1e-6)
+        }))
+    }
+
+    #[test]
+    fn test_curve_contrast() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
+        let curve_node = CurveNode{points: vec![(0.0, 0.0), (0.25, 0.1), (0.75, 0.9), (1.0, 1.0)]};
+        let image1 = node.generate(vec![]);
+        let image = curve_node.generate(vec![&image1]);
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            let input = image1.get_pixel(x, y).0[0];
+            let output = pix.0[0];
+            if input < 0.5 {
+                output <= input + 1e-6
+            } else {
+                output >= input - 1e-6
+            }
+        }));
+        for x in 0..image.width()-1 {
+            assert!(image.get_pixel(x, 0).0[0] <= image.get_pixel(x+1, 0).0[0]);
+        }
+    }
+
+    #[test]
+    fn test_curve_invalid_points() {
+        let image1 = SolidColorNode{color: Rgb([0.5, 0.5, 0.5]), properties: GeneratorProperties::default()}.generate(vec![]);
+        let curve_node = CurveNode{points: vec![(0.0, 0.0), (0.5, 0.2), (0.5, 0.8), (1.0, 1.0)]};
+        assert!(!curve_node.is_valid(&vec![&image1]));
+        let curve_node = CurveNode{points: vec![(1.0, 1.0), (0.0, 0.0)]};
+        assert!(!curve_node.is_valid(&vec![&image1]));
+        let curve_node = CurveNode{points: vec![]};
+        assert!(!curve_node.is_valid(&vec![&image1]));
+    }
+
+    fn test_mask_op_helper(op: MaskOp, expected: fn(bool, bool) -> bool) {
+        let left = Rgb32FImage::from_fn(64, 64, |x, _| if x < 32 {Rgb([1.0, 1.0, 1.0])} else {Rgb([0.0, 0.0, 0.0])});
+        let top = Rgb32FImage::from_fn(64, 64, |_, y| if y < 32 {Rgb([1.0, 1.0, 1.0])} else {Rgb([0.0, 0.0, 0.0])});
+        let mask_node = MaskOpNode{op};
+        assert!(mask_node.is_valid(&vec![&left, &top]));
+        let image = mask_node.generate(vec![&left, &top]);
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            let value = if expected(x < 32, y < 32) {1.0} else {0.0};
+            *pix == Rgb([value, value, value])
+        }))
+    }
+
+    #[test]
+    fn test_mask_and() {
+        test_mask_op_helper(MaskOp::And, |a, b| a && b);
+    }
+
+    #[test]
+    fn test_mask_or() {
+        test_mask_op_helper(MaskOp::Or, |a, b| a || b);
+    }
+
+    #[test]
+    fn test_mask_xor() {
+        test_mask_op_helper(MaskOp::Xor, |a, b| a != b);
+    }
+
+    #[test]
+    fn test_mask_subtract() {
+        test_mask_op_helper(MaskOp::Subtract, |a, b| a && !b);
+    }
+
+    #[test]
+    fn test_mask_invalid_size() {
+        let image1 = Rgb32FImage::new(64, 64);
+        let image2 = Rgb32FImage::new(64, 32);
+        assert!(!MaskOpNode{op: MaskOp::And}.is_valid(&vec![&image1, &image2]));
+    }
+
+    #[test]
+    fn test_view_identity() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 0.5, 0.25]) }, GradientNodeDirection::RADIAL, GeneratorProperties::default());
+        let view_node = ViewTransformNode{offset: (0.0, 0.0), zoom: 1.0, edge_mode: EdgeMode::Wrap};
+        let image1 = node.generate(vec![]);
+        let image = view_node.generate(vec![&image1]);
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            pix.0.iter().zip(image1.get_pixel(x, y).0).all(|(a, b)| (a - b).abs() < 1e-5)
+        }))
+    }
+
+    #[test]
+    fn test_view_zoom() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
+        let view_node = ViewTransformNode{offset: (0.0, 0.0), zoom: 2.0, edge_mode: EdgeMode::Wrap};
+        let image1 = node.generate(vec![]);
+        let image = view_node.generate(vec![&image1]);
+        let width = image.width() as f32;
+        for x in 0..image.width() {
+            let source_x = x as f32 / 2.0 + width / 4.0 - 0.25;
+            assert!((image.get_pixel(x, 0).0[0] - source_x / width).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_edge_modes() {
+        let image1 = Rgb32FImage::from_fn(4, 2, |x, y| Rgb([x as f32 / 4.0, y as f32, 0.0]));
+        let fill = Rgb([0.0, 0.0, 1.0]);
+        let expected = [
+            (EdgeMode::Clamp, [image1.get_pixel(0, 0), image1.get_pixel(3, 1)]),
+            (EdgeMode::Wrap, [image1.get_pixel(3, 0), image1.get_pixel(1, 1)]),
+            (EdgeMode::Reflect, [image1.get_pixel(0, 0), image1.get_pixel(2, 1)]),
+            (EdgeMode::Fill(fill), [&fill, &fill]),
+        ];
+        for (mode, [left, right]) in expected {
+            assert_eq!(*left, sample(&image1, -1.0, 0.0, mode));
+            assert_eq!(*right, sample(&image1, 5.0, 1.0, mode));
+            assert_eq!(*image1.get_pixel(2, 1), sample(&image1, 2.0, 1.0, mode));
+        }
+        // Between the last pixel and the position just outside the image, wrapping interpolates towards the first pixel.
+        let between = sample(&image1, 3.5, 0.0, EdgeMode::Wrap);
+        assert!((between.0[0] - 0.375).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sharpen_identity() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.2, 0.4]), end: Rgb([1.0, 0.8, 0.6]) }, GradientNodeDirection::RADIAL, GeneratorProperties::default());
+        let image1 = node.generate(vec![]);
+        let image = SharpenNode{amount: 0.0, edge_mode: EdgeMode::Clamp}.generate(vec![&image1]);
+        assert_eq!(image1, image);
+    }
+
+    #[test]
+    fn test_sharpen_edge_contrast() {
+        let image1 = Rgb32FImage::from_fn(8, 8, |x, _| if x < 4 {Rgb([0.25, 0.25, 0.25])} else {Rgb([0.75, 0.75, 0.75])});
+        let image = SharpenNode{amount: 0.5, edge_mode: EdgeMode::Clamp}.generate(vec![&image1]);
+        let before = image1.get_pixel(4, 4).0[0] - image1.get_pixel(3, 4).0[0];
+        let after = image.get_pixel(4, 4).0[0] - image.get_pixel(3, 4).0[0];
+        assert!(after > before);
+        // Away from the edge the image is unchanged.
+        assert_eq!(image1.get_pixel(0, 4), image.get_pixel(0, 4));
+        assert_eq!(image1.get_pixel(7, 4), image.get_pixel(7, 4));
+    }
+
+    #[test]
+    fn test_box_blur_wrap() {
+        let tile = Rgb32FImage::from_fn(16, 8, |x, y| Rgb([((x * 7 + y * 13) % 11) as f32 / 10.0, (x % 3) as f32 / 2.0, (y % 4) as f32 / 3.0]));
+        let tiled = Rgb32FImage::from_fn(48, 24, |x, y| *tile.get_pixel(x % 16, y % 8));
+        let node = BoxBlurNode{radius: 3, edge_mode: EdgeMode::Wrap};
+        let image = node.generate(vec![&tile]);
+        // The edges of the wrapped blur match the middle tile of a blurred 3 by 3 tiling, which has real neighbours there.
+        let expected = BoxBlurNode{radius: 3, edge_mode: EdgeMode::Clamp}.generate(vec![&tiled]);
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            pix.0.iter().zip(expected.get_pixel(x + 16, y + 8).0).all(|(a, b)| (a - b).abs() < 1e-5)
+        }));
+        let clamped = BoxBlurNode{radius: 3, edge_mode: EdgeMode::Clamp}.generate(vec![&tile]);
+        assert_ne!(clamped.get_pixel(0, 0), image.get_pixel(0, 0));
+        assert_eq!(tile, BoxBlurNode{radius: 0, edge_mode: EdgeMode::Wrap}.generate(vec![&tile]));
+    }
+
+    #[test]
+    fn test_motion_blur_identity() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.2, 0.4]), end: Rgb([1.0, 0.8, 0.6]) }, GradientNodeDirection::RADIAL, GeneratorProperties::default());
+        let image1 = node.generate(vec![]);
+        let image = MotionBlurNode{angle: 0.7, length: 1, edge_mode: EdgeMode::Clamp}.generate(vec![&image1]);
+        assert_eq!(image1, image);
+    }
+
+    #[test]
+    fn test_motion_blur_point() {
+        let mut image1 = Rgb32FImage::new(16, 16);
+        image1.put_pixel(8, 8, Rgb([1.0, 1.0, 1.0]));
+        let horizontal = MotionBlurNode{angle: 0.0, length: 5, edge_mode: EdgeMode::Clamp}.generate(vec![&image1]);
+        assert!(horizontal.enumerate_pixels().all(|(x, y, pix)| {
+            let expected = if y == 8 && (6..=10).contains(&x) {0.2} else {0.0};
+            (pix.0[0] - expected).abs() < 1e-6
+        }));
+        let diagonal = MotionBlurNode{angle: std::f32::consts::FRAC_PI_4, length: 5, edge_mode: EdgeMode::Clamp}.generate(vec![&image1]);
+        assert!(diagonal.enumerate_pixels().all(|(x, y, pix)| {
+            // Samples along the diagonal are a pixel length apart, so the five samples cover three diagonal pixels.
+            let on_line = x == y && (7..=9).contains(&x);
+            (pix.0[0] > 0.0) == on_line
+        }));
+    }
+
+    fn test_dither_helper(method: DitherMethod) {
+        let node = GradientNode::new(Gradient { start: Rgb([0.3, 0.3, 0.3]), end: Rgb([0.4, 0.4, 0.4]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
+        let image1 = node.generate(vec![]);
+        let dither_node = DitherNode{levels: 4, method};
+        assert!(dither_node.is_valid(&vec![&image1]));
+        let image = dither_node.generate(vec![&image1]);
+        let mean = |img: &Rgb32FImage| img.as_raw().iter().sum::<f32>() / img.as_raw().len() as f32;
+        assert!((mean(&image1) - mean(&image)).abs() < 0.01);
+        assert!(image.as_raw().iter().all(|v| [0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0].iter().any(|level| (v - level).abs() < 1e-6)));
+    }
+
+    #[test]
+    fn test_dither_ordered() {
+        test_dither_helper(DitherMethod::Ordered);
+    }
+
+    #[test]
+    fn test_dither_floyd_steinberg() {
+        test_dither_helper(DitherMethod::FloydSteinberg);
+    }
+
+    #[test]
+    fn test_dither_invalid_levels() {
+        let image1 = Rgb32FImage::new(8, 8);
+        assert!(!DitherNode{levels: 1, method: DitherMethod::Ordered}.is_valid(&vec![&image1]));
+    }
+
+    #[test]
+    fn test_dither_zero_levels() {
+        let image1 = Rgb32FImage::from_pixel(8, 8, Rgb([0.2, 0.5, 0.9]));
+        for method in [DitherMethod::Ordered, DitherMethod::FloydSteinberg] {
+            let node = DitherNode{levels: 0, method};
+            assert!(!node.is_valid(&vec![&image1]));
+            let image = node.generate(vec![&image1]);
+            assert!(image.as_raw().iter().all(|&v| v == 0.0 || v == 1.0));
+        }
+    }
+
+    #[test]
+    fn test_pixel_map_invert() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.2, 0.4]), end: Rgb([1.0, 0.8, 0.6]) }, GradientNodeDirection::RADIAL, GeneratorProperties::default());
+        let image1 = node.generate(vec![]);
+        let map_node = PixelMapNode{f: |pix: Color| Rgb(pix.0.map(|v| 1.0 - v)), key: 0};
+        let invert_node = CurveNode{points: vec![(0.0, 1.0), (1.0, 0.0)]};
+        assert_eq!(1, map_node.inputs());
+        let image = map_node.generate(vec![&image1]);
+        let expected = invert_node.generate(vec![&image1]);
+        assert!(image.pixels().zip(expected.pixels()).all(|(a, b)| {
+            a.0.iter().zip(b.0).all(|(a, b)| (a - b).abs() < 1e-6)
+        }));
+        assert_eq!(image, map_node.clone_box().generate(vec![&image1]));
+    }
+
+    #[test]
+    fn test_pixel_map2_add() {
+        let node1 = GradientNode::new(Gradient { start: Rgb([0.0, 0.2, 0.4]), end: Rgb([1.0, 0.8, 0.6]) }, GradientNodeDirection::RADIAL, GeneratorProperties::default());
+        let node2 = SolidColorNode{color: Rgb([0.3, 0.5, 0.1]), properties: GeneratorProperties::default()};
+        let image1 = node1.generate(vec![]);
+        let image2 = node2.generate(vec![]);
+        let map_node = PixelMap2Node{f: |a: Color, b: Color| Rgb([0, 1, 2].map(|c| (a.0[c] + b.0[c]).clamp(0.0, 1.0))), key: 0};
+        assert_eq!(2, map_node.inputs());
+        assert!(map_node.is_valid(&vec![&image1, &image2]));
+        let expected = BlendNode::new(BlendOptions::Add).generate(vec![&image1, &image2]);
+        assert_eq!(expected, map_node.generate(vec![&image1, &image2]));
+    }
+
+    #[test]
+    fn test_pixel_map2_invalid_size() {
+        let image1 = Rgb32FImage::new(64, 64);
+        let image2 = Rgb32FImage::new(32, 64);
+        let map_node = PixelMap2Node{f: |a: Color, _: Color| a, key: 0};
+        assert!(!map_node.is_valid(&vec![&image1, &image2]));
+    }
+
+    /// Returns the sum of the absolute second differences along the first row, which is lower for smoother images.
+    fn second_difference(image: &Rgb32FImage) -> f32 {
+        (1..image.width() - 1)
+            .map(|x| (image.get_pixel(x - 1, 0).0[0] - 2.0 * image.get_pixel(x, 0).0[0] + image.get_pixel(x + 1, 0).0[0]).abs())
+            .sum()
+    }
+
+    #[test]
+    fn test_scale_bicubic_smooth() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties { width: 8, height: 8 });
+        let image1 = node.generate(vec![]);
+        let scale = |filter| ScaleNode{width: 64, height: 64, filter, edge_mode: EdgeMode::Clamp}.generate(vec![&image1]);
+        let nearest = scale(ScaleFilter::Nearest);
+        let bilinear = scale(ScaleFilter::Bilinear);
+        let bicubic = scale(ScaleFilter::Bicubic);
+        assert_eq!((64, 64), bicubic.dimensions());
+        assert!(second_difference(&bicubic) < second_difference(&nearest));
+        assert!(second_difference(&bilinear) < second_difference(&nearest));
+        // Next to the clamped edges the spline overshoots the input range by less than a tenth of the step between input pixels.
+        let max = image1.get_pixel(7, 0).0[0];
+        let tolerance = 0.1 / 8.0;
+        assert!(bicubic.pixels().all(|pix| pix.0[0] >= -tolerance && pix.0[0] <= max + tolerance));
+    }
+
+    #[test]
+    fn test_scale_identity() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.2, 0.4]), end: Rgb([1.0, 0.8, 0.6]) }, GradientNodeDirection::RADIAL, GeneratorProperties::default());
+        let image1 = node.generate(vec![]);
+        for filter in [ScaleFilter::Nearest, ScaleFilter::Bilinear, ScaleFilter::Bicubic] {
+            let image = ScaleNode{width: 128, height: 128, filter, edge_mode: EdgeMode::Clamp}.generate(vec![&image1]);
+            assert!(image.pixels().zip(image1.pixels()).all(|(a, b)| a.0.iter().zip(b.0).all(|(a, b)| (a - b).abs() < 1e-5)));
+        }
+        assert!(!ScaleNode{width: 0, height: 128, filter: ScaleFilter::Nearest, edge_mode: EdgeMode::Clamp}.is_valid(&vec![&image1]));
+    }
+
+    fn solid_pair() -> (Rgb32FImage, Rgb32FImage) {
+        (Rgb32FImage::from_pixel(8, 8, Rgb([0.2, 0.9, 0.5])), Rgb32FImage::from_pixel(8, 8, Rgb([0.6, 0.1, 0.5])))
+    }
+
+    #[test]
+    fn test_min() {
+        let (image1, image2) = solid_pair();
+        let image = MinNode{}.generate(vec![&image1, &image2]);
+        assert!(image.pixels().all(|pix| *pix == Rgb([0.2, 0.1, 0.5])));
+        assert!(!MinNode{}.is_valid(&vec![&image1, &Rgb32FImage::new(4, 8)]));
+    }
+
+    #[test]
+    fn test_max() {
+        let (image1, image2) = solid_pair();
+        let image = MaxNode{}.generate(vec![&image1, &image2]);
+        assert!(image.pixels().all(|pix| *pix == Rgb([0.6, 0.9, 0.5])));
+        assert!(!MaxNode{}.is_valid(&vec![&image1, &Rgb32FImage::new(4, 8)]));
+    }
+
+    #[test]
+    fn test_lerp() {
+        let (image1, image2) = solid_pair();
+        let lerp = |t: f32, expected: [f32; 3]| {
+            let image = LerpNode{t}.generate(vec![&image1, &image2]);
+            image.pixels().all(|pix| pix.0.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-6))
+        };
+        assert!(lerp(0.0, [0.2, 0.9, 0.5]));
+        assert!(lerp(1.0, [0.6, 0.1, 0.5]));
+        assert!(lerp(0.25, [0.3, 0.7, 0.5]));
+        assert!(!LerpNode{t: 0.5}.is_valid(&vec![&image1, &Rgb32FImage::new(4, 8)]));
+    }
+
+    fn assert_solid(image: &Rgb32FImage, expected: [f32; 3]) {
+        assert!(image.pixels().all(|pix| pix.0.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-6)));
+    }
+
+    #[test]
+    fn test_combine_average() {
+        let images = [[0.3, 0.0, 0.9], [0.6, 0.3, 0.0], [0.0, 0.6, 0.3]].map(|c| Rgb32FImage::from_pixel(8, 8, Rgb(c)));
+        let node = CombineNode{count: 3, op: CombineOp::Average};
+        assert_eq!(3, node.inputs());
+        let inputs: Vec<_> = images.iter().collect();
+        assert!(node.is_valid(&inputs));
+        assert_solid(&node.generate(inputs.clone()), [0.3, 0.3, 0.4]);
+        assert_solid(&CombineNode{count: 3, op: CombineOp::Max}.generate(inputs.clone()), [0.6, 0.6, 0.9]);
+        assert_solid(&CombineNode{count: 3, op: CombineOp::Min}.generate(inputs), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_combine_sum() {
+        let images = [[0.1, 0.2, 0.5], [0.1, 0.2, 0.5], [0.1, 0.2, 0.5], [0.1, 0.2, 0.5]].map(|c| Rgb32FImage::from_pixel(8, 8, Rgb(c)));
+        let node = CombineNode{count: 4, op: CombineOp::Sum};
+        let inputs: Vec<_> = images.iter().collect();
+        assert_solid(&node.generate(inputs.clone()), [0.4, 0.8, 1.0]);
+        assert!(!CombineNode{count: 3, op: CombineOp::Sum}.is_valid(&inputs));
+        let other = Rgb32FImage::new(4, 8);
+        assert!(!node.is_valid(&vec![inputs[0], inputs[1], inputs[2], &other]));
+    }
+
+    #[test]
+    fn test_luminance_to_mask() {
+        let white = Rgb32FImage::from_pixel(8, 8, Rgb([1.0, 1.0, 1.0]));
+        let black = Rgb32FImage::new(8, 8);
+        assert_solid(&LuminanceToMaskNode{invert: false}.generate(vec![&white]), [1.0, 1.0, 1.0]);
+        assert_solid(&LuminanceToMaskNode{invert: true}.generate(vec![&black]), [1.0, 1.0, 1.0]);
+        let green = Rgb32FImage::from_pixel(8, 8, Rgb([0.0, 1.0, 0.0]));
+        assert_solid(&LuminanceToMaskNode{invert: false}.generate(vec![&green]), [0.7152, 0.7152, 0.7152]);
+    }
+
+    #[test]
+    fn test_size_inference() {
+        let mut graph = TextureGraph::new();
+        let red = SolidColorNode{color: Rgb([1.0, 0.0, 0.0]), properties: GeneratorProperties::square(64).unwrap()};
+        let blue = SolidColorNode{color: Rgb([0.0, 0.0, 1.0]), properties: GeneratorProperties::square(32).unwrap()};
+        let index1 = graph.add_node(Node::new(String::from("Red"), Box::new(red)));
+        let index2 = graph.add_node(Node::new(String::from("Blue"), Box::new(blue)));
+        let scale = graph.add_node(Node::new(String::from("Scale"), Box::new(ScaleNode{width: 32, height: 32, filter: ScaleFilter::Nearest, edge_mode: EdgeMode::Clamp})));
+        let blend = graph.add_node(Node::new(String::from("Blend"), Box::new(BlendNode::new(BlendOptions::Add))));
+        graph.add_edge(index1, scale, 0).unwrap();
+        graph.add_edge(index1, blend, 0).unwrap();
+        graph.add_edge(index2, blend, 1).unwrap();
+        assert_eq!(Err(vec![GraphError::SizeMismatch { node: blend, sizes: vec![(64, 64), (32, 32)] }]), graph.validate());
+        let sizes = graph.infer_sizes();
+        assert_eq!(Some(&(32, 32)), sizes.get(&scale));
+        assert!(!sizes.contains_key(&blend));
+        assert!(graph.get_generated_node(&index1).is_none());
+        graph.add_edge(scale, blend, 0).unwrap();
+        assert_eq!(Ok(()), graph.validate());
+        assert_eq!(Some(&(32, 32)), graph.infer_sizes().get(&blend));
+    }
+
+    #[test]
+    fn test_crop() {
+        let node = GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default());
+        let image1 = node.generate(vec![]);
+        let crop = CropNode{x: 0, y: 0, width: 64, height: 64};
+        assert!(crop.is_valid(&vec![&image1]));
+        let image = crop.generate(vec![&image1]);
+        assert_eq!((64, 64), image.dimensions());
+        assert_eq!(image1.get_pixel(63, 63), image.get_pixel(63, 63));
+        // Regions past the edge of the input are clamped, and regions fully outside of it are invalid.
+        let clamped = CropNode{x: 100, y: 120, width: 64, height: 64};
+        assert_eq!((28, 8), clamped.generate(vec![&image1]).dimensions());
+        assert_eq!(image1.get_pixel(127, 127), clamped.generate(vec![&image1]).get_pixel(27, 7));
+        assert!(!CropNode{x: 128, y: 0, width: 64, height: 64}.is_valid(&vec![&image1]));
+        assert!(!CropNode{x: 0, y: 0, width: 0, height: 64}.is_valid(&vec![&image1]));
+    }
+
+    #[test]
+    fn test_pad() {
+        let image1 = Rgb32FImage::from_pixel(64, 64, Rgb([1.0, 0.0, 0.0]));
+        let fill = Rgb([0.0, 0.0, 1.0]);
+        let image = PadNode{left: 10, right: 10, top: 10, bottom: 10, fill}.generate(vec![&image1]);
+        assert_eq!((84, 84), image.dimensions());
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            let inside = (10..74).contains(&x) && (10..74).contains(&y);
+            *pix == if inside { image1.get_pixel(x - 10, y - 10).to_owned() } else { fill }
+        }));
+        let uneven = PadNode{left: 1, right: 2, top: 0, bottom: 3, fill};
+        assert_eq!((67, 67), uneven.generate(vec![&image1]).dimensions());
+        assert_eq!(Some((67, 67)), uneven.output_size(&[(64, 64)]));
+        // Borders that do not fit in the size of an image are invalid instead of overflowing.
+        let huge = PadNode{left: u32::MAX, right: 0, top: 0, bottom: 0, fill};
+        assert_eq!(None, huge.output_size(&[(64, 64)]));
+        assert!(!huge.is_valid(&vec![&image1]));
+        assert_eq!(image1, huge.generate(vec![&image1]));
+    }
+
+    #[test]
+    fn test_params_hash() {
+        let dither = DitherNode{levels: 4, method: DitherMethod::Ordered};
+        assert_eq!(dither.params_hash(), dither.clone().params_hash());
+        assert_ne!(dither.params_hash(), DitherNode{levels: 5, method: DitherMethod::Ordered}.params_hash());
+        assert_ne!(dither.params_hash(), DitherNode{levels: 4, method: DitherMethod::FloydSteinberg}.params_hash());
+        let fill = Rgb([0.0, 0.0, 1.0]);
+        let pad = PadNode{left: 1, right: 2, top: 3, bottom: 4, fill};
+        // Differences too small to show up in the description still change the hash.
+        assert_ne!(pad.params_hash(), PadNode{fill: Rgb([0.0, 0.0, 0.999]), ..pad.clone()}.params_hash());
+        let invert = PixelMapNode{f: |pix: Color| Rgb(pix.0.map(|v| 1.0 - v)), key: 0};
+        let identity = PixelMapNode{f: |pix: Color| pix, key: 0};
+        assert_ne!(invert.params_hash(), identity.params_hash());
+        let scale = |factor: f32| move |pix: Color| Rgb(pix.0.map(|v| v * factor));
+        assert_eq!(PixelMapNode{f: scale(0.5), key: 1}.params_hash(), PixelMapNode{f: scale(0.5), key: 1}.params_hash());
+        assert_ne!(PixelMapNode{f: scale(0.5), key: 1}.params_hash(), PixelMapNode{f: scale(2.0), key: 2}.params_hash());
+    }
+
+    #[test]
+    fn test_input_names() {
+        let blend = BlendNode::new(BlendOptions::Mask(0.5, Brightness::Luminance));
+        assert_eq!(vec!["A", "B"], blend.input_names());
+        assert_eq!(vec!["base", "detail"], NormalBlendNode{method: NormalBlend::Linear}.input_names());
+        assert_eq!(vec!["input 0", "input 1", "input 2"], CombineNode{count: 3, op: CombineOp::Sum}.input_names());
+        assert_eq!("input 19", CombineNode{count: 20, op: CombineOp::Sum}.input_names()[19]);
+    }
+
+    #[test]
+    fn test_normal_blend() {
+        let neutral = Rgb32FImage::from_pixel(8, 8, Rgb([0.5, 0.5, 1.0]));
+        let (tilt_x, tilt_z) = (0.6_f32, 0.8_f32);
+        let tilted = Rgb32FImage::from_pixel(8, 8, Rgb([tilt_x * 0.5 + 0.5, 0.5, tilt_z * 0.5 + 0.5]));
+        for method in [NormalBlend::Linear, NormalBlend::ReorientedNormalMapping] {
+            let node = NormalBlendNode{method};
+            assert!(node.is_valid(&vec![&neutral, &tilted]));
+            assert!(node.generate(vec![&neutral, &neutral]).pixels().all(|pix| *pix == Rgb([0.5, 0.5, 1.0])));
+            // Every combined normal leans towards the tilt, and is a unit vector again.
+            for image in [node.generate(vec![&neutral, &tilted]), node.generate(vec![&tilted, &neutral])] {
+                assert!(image.pixels().all(|pix| {
+                    let [x, y, z] = pix.0.map(|v| v * 2.0 - 1.0);
+                    x > 0.1 && y.abs() < 1e-6 && (x * x + y * y + z * z - 1.0).abs() < 1e-5
+                }));
+            }
+        }
+        // Reoriented normal mapping keeps the full tilt of a detail on a flat surface, and of a flat detail on a tilted surface.
+        let node = NormalBlendNode{method: NormalBlend::ReorientedNormalMapping};
+        for image in [node.generate(vec![&neutral, &tilted]), node.generate(vec![&tilted, &neutral])] {
+            assert!(image.pixels().all(|pix| pix.0.iter().zip(tilted.get_pixel(0, 0).0).all(|(a, b)| (a - b).abs() < 1e-5)));
+        }
+        assert!(!node.is_valid(&vec![&neutral, &Rgb32FImage::new(4, 4)]));
+    }
+
+    #[test]
+    fn test_bloom() {
+        let node = BloomNode{threshold: 0.5, radius: 2.0, intensity: 1.0};
+        let dim = Rgb32FImage::from_pixel(16, 16, Rgb([0.2, 0.3, 0.1]));
+        assert_eq!(dim, node.generate(vec![&dim]));
+        let mut spot = Rgb32FImage::new(32, 32);
+        spot.put_pixel(16, 16, Rgb([1.0, 1.0, 1.0]));
+        let image = node.generate(vec![&spot]);
+        assert_eq!(Rgb([1.0, 1.0, 1.0]), *image.get_pixel(16, 16));
+        for (x, y) in [(15, 16), (18, 16), (16, 19)] {
+            assert!(image.get_pixel(x, y).0.iter().all(|&v| v > 0.0), "no glow at ({}, {})", x, y);
+        }
+        assert!(image.get_pixel(17, 16)[0] > image.get_pixel(19, 16)[0]);
+        assert_eq!(Rgb([0.0, 0.0, 0.0]), *image.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_flood_recolor() {
+        let black = Rgb([0.0, 0.0, 0.0]);
+        let white = Rgb([1.0, 1.0, 1.0]);
+        let red = Rgb([1.0, 0.0, 0.0]);
+        // Tiles of 4 by 4 pixels, where tiles of the same color only touch at their corners.
+        let checkerboard = Rgb32FImage::from_fn(16, 16, |x, y| if (x / 4 + y / 4) % 2 == 0 { black } else { white });
+        let node = FloodRecolorNode{tolerance: 0.1, replacement: red, seed: (5, 1)};
+        assert!(node.is_valid(&vec![&checkerboard]));
+        let image = node.generate(vec![&checkerboard]);
+        assert!(image.enumerate_pixels().all(|(x, y, pix)| {
+            let in_tile = (4..8).contains(&x) && y < 4;
+            *pix == if in_tile { red } else { *checkerboard.get_pixel(x, y) }
+        }));
+        // A tolerance that includes both colors fills the whole image.
+        let everything = FloodRecolorNode{tolerance: 1.0, ..node.clone()}.generate(vec![&checkerboard]);
+        assert!(everything.pixels().all(|pix| *pix == red));
+        assert!(!FloodRecolorNode{seed: (16, 0), ..node}.is_valid(&vec![&checkerboard]));
+    }
+
+    #[test]
+    fn test_average_color() {
+        let two_tone = Rgb32FImage::from_fn(32, 16, |x, _| if x < 16 { Rgb([1.0, 0.0, 0.2]) } else { Rgb([0.0, 0.5, 0.6]) });
+        let image = AverageColorNode{}.generate(vec![&two_tone]);
+        assert_eq!((32, 16), image.dimensions());
+        assert!(image.pixels().all(|pix| pix.0.iter().zip([0.5, 0.25, 0.4]).all(|(a, b)| (a - b).abs() < 1e-6)));
+    }
+
+    #[test]
+    fn test_probe() {
+        let mut graph = TextureGraph::<Rgb32FImage>::new();
+        let gradient = graph.add_node(Node::new(String::from("Gradient"), Box::new(GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 1.0]), end: Rgb([1.0, 0.0, 1.0]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default()))));
+        let probe = graph.add_node(Node::new(String::from("Probe"), Box::new(ProbeNode{})));
+        graph.add_edge(gradient, probe, 0).unwrap();
+        graph.generate_graph().unwrap();
+        let stats = graph.probe_stats(probe).unwrap();
+        assert_eq!(vec![0.0, 0.0, 1.0], stats.min);
+        assert!((stats.max[0] - 1.0).abs() < 0.01);
+        assert_eq!(&stats.max[1..], &[0.0, 1.0]);
+        assert!((stats.mean[0] - 0.5).abs() < 0.01);
+        assert_eq!(&stats.mean[1..], &[0.0, 1.0]);
+        // The input passes through unchanged.
+        let input = graph.get_generated_node(&gradient).unwrap().clone();
+        assert_eq!(Some(&input), graph.get_generated_node(&probe));
+    }
+}
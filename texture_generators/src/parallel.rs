@@ -0,0 +1,45 @@
+use core::Color;
+
+use image::Rgb32FImage;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Builds an image by calling a function for every pixel, like `ImageBuffer::from_fn`.
+/// With the `rayon` feature the rows are filled in parallel. Every pixel only depends on its own position,
+/// so the result is the same as building the image serially.
+pub fn par_from_fn(width: u32, height: u32, f: impl Fn(u32, u32) -> Color + Sync) -> Rgb32FImage {
+    #[cfg(feature = "rayon")]
+    {
+        let mut buffer = vec![0.0; width as usize * height as usize * 3];
+        if width > 0 {
+            buffer.par_chunks_mut(width as usize * 3).enumerate().for_each(|(y, row)| {
+                for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+                    pixel.copy_from_slice(&f(x as u32, y as u32).0);
+                }
+            });
+        }
+        Rgb32FImage::from_raw(width, height, buffer).unwrap()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        Rgb32FImage::from_fn(width, height, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, Rgb32FImage};
+
+    use crate::par_from_fn;
+
+    #[test]
+    fn matches_serial() {
+        let f = |x: u32, y: u32| {
+            let u = x as f32 / 512.0 - 0.5;
+            let v = y as f32 / 512.0 - 0.5;
+            Rgb([(u * u + v * v).sqrt(), u.sin(), (x ^ y) as f32 / 512.0])
+        };
+        assert_eq!(Rgb32FImage::from_fn(512, 512, f), par_from_fn(512, 512, f));
+        assert_eq!((0, 4), par_from_fn(0, 4, f).dimensions());
+    }
+}
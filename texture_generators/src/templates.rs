@@ -0,0 +1,103 @@
+//! Ready-made graphs that show how nodes can be combined into complete textures.
+use core::Gradient;
+
+use graph::{TextureGraph, TextureTransformer, Node, NodeIndex};
+use image::{Rgb, Rgb32FImage};
+
+use crate::{BlendNode, BlendOptions, CheckerboardNode, CurveNode, GeneratorProperties, GradientNode, GradientNodeDirection,
+    LinesNode, LinesPosition, LineOrientation, SolidColorNode};
+
+fn add(graph: &mut TextureGraph<Rgb32FImage>, name: &str, function: Box<dyn TextureTransformer<Rgb32FImage>>) -> NodeIndex {
+    graph.add_node(Node::new(String::from(name), function))
+}
+
+/// Red bricks with slightly varying colors, separated by horizontal mortar lines.
+pub fn brick_wall() -> TextureGraph<Rgb32FImage> {
+    let mut graph = TextureGraph::new();
+    let bricks = add(&mut graph, "Bricks", Box::new(CheckerboardNode::new(3, 7, Rgb([0.7, 0.25, 0.15]), Rgb([0.6, 0.2, 0.12]), GeneratorProperties::default())));
+    let mortar = add(&mut graph, "Mortar", Box::new(LinesNode {
+        scale: 8,
+        thickness: 0.1,
+        position: LinesPosition::Start,
+        orientation: LineOrientation::Horizontal,
+        color1: Rgb([1.0, 1.0, 1.0]),
+        color2: Rgb([0.8, 0.8, 0.75]),
+        properties: GeneratorProperties::default()
+    }));
+    let wall = add(&mut graph, "Wall", Box::new(BlendNode::new(BlendOptions::Multiply)));
+    graph.add_edge(bricks, wall, 0).unwrap();
+    graph.add_edge(mortar, wall, 1).unwrap();
+    graph
+}
+
+/// A gray metal plate with rust spreading from the corners.
+pub fn rusty_metal() -> TextureGraph<Rgb32FImage> {
+    let mut graph = TextureGraph::new();
+    let spread = add(&mut graph, "Rust spread", Box::new(GradientNode::new(Gradient { start: Rgb([0.0, 0.0, 0.0]), end: Rgb([1.0, 1.0, 1.0]) }, GradientNodeDirection::RADIAL, GeneratorProperties::default())));
+    let falloff = add(&mut graph, "Rust falloff", Box::new(CurveNode {
+        points: vec![(0.0, 0.0), (0.4, 0.1), (0.7, 0.9), (1.0, 1.0)]
+    }));
+    let rust = add(&mut graph, "Rust", Box::new(SolidColorNode {
+        color: Rgb([0.55, 0.25, 0.1]),
+        properties: GeneratorProperties::default()
+    }));
+    let metal = add(&mut graph, "Metal", Box::new(GradientNode::new(Gradient { start: Rgb([0.55, 0.55, 0.6]), end: Rgb([0.4, 0.4, 0.45]) }, GradientNodeDirection::VERTICAL, GeneratorProperties::default())));
+    let rust_mask = add(&mut graph, "Rust mask", Box::new(BlendNode::new(BlendOptions::Multiply)));
+    let result = add(&mut graph, "Rusty metal", Box::new(BlendNode::new(BlendOptions::Add)));
+    graph.add_edge(spread, falloff, 0).unwrap();
+    graph.add_edge(falloff, rust_mask, 0).unwrap();
+    graph.add_edge(rust, rust_mask, 1).unwrap();
+    graph.add_edge(metal, result, 0).unwrap();
+    graph.add_edge(rust_mask, result, 1).unwrap();
+    graph
+}
+
+/// Horizontal wooden planks with a darkening grain across the width.
+pub fn wood_planks() -> TextureGraph<Rgb32FImage> {
+    let mut graph = TextureGraph::new();
+    let planks = add(&mut graph, "Planks", Box::new(LinesNode {
+        scale: 6,
+        thickness: 0.05,
+        position: LinesPosition::End,
+        orientation: LineOrientation::Horizontal,
+        color1: Rgb([0.6, 0.4, 0.2]),
+        color2: Rgb([0.25, 0.15, 0.08]),
+        properties: GeneratorProperties::default()
+    }));
+    let grain = add(&mut graph, "Grain", Box::new(GradientNode::new(Gradient { start: Rgb([1.0, 1.0, 1.0]), end: Rgb([0.75, 0.7, 0.65]) }, GradientNodeDirection::HORIZONTAL, GeneratorProperties::default())));
+    let wood = add(&mut graph, "Wood", Box::new(BlendNode::new(BlendOptions::Multiply)));
+    graph.add_edge(planks, wood, 0).unwrap();
+    graph.add_edge(grain, wood, 1).unwrap();
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::TextureGraph;
+    use image::Rgb32FImage;
+
+    use crate::templates::{brick_wall, rusty_metal, wood_planks};
+
+    fn test_template_helper(mut graph: TextureGraph<Rgb32FImage>) {
+        assert!(graph.graph_complete());
+        assert!(graph.generate_graph().is_ok());
+        for index in graph.output_nodes() {
+            assert!(graph.get_generated_node(&index).is_some());
+        }
+    }
+
+    #[test]
+    fn test_brick_wall() {
+        test_template_helper(brick_wall());
+    }
+
+    #[test]
+    fn test_rusty_metal() {
+        test_template_helper(rusty_metal());
+    }
+
+    #[test]
+    fn test_wood_planks() {
+        test_template_helper(wood_planks());
+    }
+}